@@ -0,0 +1,60 @@
+/// Apply a serde `rename_all` rule to a field name, following the same rules as serde itself.
+///
+/// Returns `None` if the rule isn't one that serde supports.
+pub fn rename_field(rule: &str, field: &str) -> Option<String> {
+    let renamed = match rule {
+        "lowercase" | "snake_case" => field.to_owned(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => field.to_ascii_uppercase(),
+        "PascalCase" => pascal_case(field),
+        "camelCase" => {
+            let pascal = pascal_case(field);
+            let mut chars = pascal.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_ascii_lowercase().to_string() + chars.as_str()
+            })
+        },
+        "kebab-case" => field.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => field.to_ascii_uppercase().replace('_', "-"),
+        _ => return None,
+    };
+
+    Some(renamed)
+}
+
+fn pascal_case(field: &str) -> String {
+    let mut result = String::new();
+    let mut capitalize = true;
+
+    for c in field.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            result.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::check;
+
+    use super::*;
+
+    #[test]
+    fn rules() {
+        check!(rename_field("lowercase", "user_name").as_deref() == Some("user_name"));
+        check!(rename_field("UPPERCASE", "user_name").as_deref() == Some("USER_NAME"));
+        check!(rename_field("PascalCase", "user_name").as_deref() == Some("UserName"));
+        check!(rename_field("camelCase", "user_name").as_deref() == Some("userName"));
+        check!(rename_field("snake_case", "user_name").as_deref() == Some("user_name"));
+        check!(rename_field("SCREAMING_SNAKE_CASE", "user_name").as_deref() == Some("USER_NAME"));
+        check!(rename_field("kebab-case", "user_name").as_deref() == Some("user-name"));
+        check!(rename_field("SCREAMING-KEBAB-CASE", "user_name").as_deref() == Some("USER-NAME"));
+        check!(rename_field("Title Case", "user_name").is_none());
+    }
+}
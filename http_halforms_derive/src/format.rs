@@ -0,0 +1,97 @@
+use syn::LitStr;
+
+/// The placeholders used in an href format string.
+#[derive(Debug, Default)]
+pub struct Placeholders {
+    /// Whether the string contains a `{}` placeholder for the value of the field itself.
+    pub positional: bool,
+
+    /// The names of the other fields referenced by the string, in order of first use.
+    pub named: Vec<String>,
+}
+
+/// Parse the placeholders out of an href format string, following the rules of `format!`.
+pub fn placeholders(href: &LitStr) -> syn::Result<Placeholders> {
+    let value = href.value();
+    let mut chars = value.chars().peekable();
+    let mut result = Placeholders::default();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+            },
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => {
+                            return Err(syn::Error::new_spanned(href, "unterminated `{` in href"))
+                        },
+                    }
+                }
+
+                let name = placeholder.split(':').next().unwrap_or_default().trim();
+                if name.is_empty() {
+                    result.positional = true;
+                } else if name.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(syn::Error::new_spanned(
+                        href,
+                        "numbered placeholders are not supported in href",
+                    ));
+                } else if !result.named.iter().any(|n| n == name) {
+                    result.named.push(name.to_owned());
+                }
+            },
+            '}' => return Err(syn::Error::new_spanned(href, "unmatched `}` in href")),
+            _ => {},
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+    use proc_macro2::Span;
+
+    use super::*;
+
+    fn parse(href: &str) -> syn::Result<Placeholders> {
+        placeholders(&LitStr::new(href, Span::call_site()))
+    }
+
+    #[test]
+    fn no_placeholders() {
+        let_assert!(Ok(result) = parse("/orders"));
+        check!(!result.positional);
+        check!(result.named.is_empty());
+    }
+
+    #[test]
+    fn positional_and_named() {
+        let_assert!(Ok(result) = parse("/orders/{id}/lines/{}/{id:>4}"));
+        check!(result.positional);
+        check!(result.named == vec!["id".to_owned()]);
+    }
+
+    #[test]
+    fn escaped_braces() {
+        let_assert!(Ok(result) = parse("/search{{?q}}"));
+        check!(!result.positional);
+        check!(result.named.is_empty());
+    }
+
+    #[test]
+    fn invalid() {
+        check!(parse("/orders/{id").is_err());
+        check!(parse("/orders/}").is_err());
+        check!(parse("/orders/{0}").is_err());
+    }
+}
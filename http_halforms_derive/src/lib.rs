@@ -0,0 +1,70 @@
+//! Derive macros for the [`http_halforms`](https://crates.io/crates/http_halforms) crate.
+//!
+//! These are re-exported from `http_halforms` when the `derive` feature is enabled, and should be
+//! used from there rather than depending on this crate directly.
+
+mod case;
+mod format;
+mod resource;
+mod template;
+mod utils;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+/// Derive an implementation of `HalResource` for a struct with named fields.
+///
+/// The struct is converted into a `Hal` document whose payload is a generated `<Name>HalPayload`
+/// struct containing all of the fields that aren't otherwise marked. Any `#[serde(...)]`
+/// attributes on these fields, and `#[serde(rename_all = "...")]` on the struct, are carried over.
+///
+/// Supported attributes:
+/// - `#[hal(self_href = "/orders/{id}")]` on the struct adds a `self` link.
+/// - `#[hal(link = "customer", href = "/customers/{}")]` on a field renders it as a link instead of
+///   a payload field.
+/// - `#[hal(embedded = "lines")]` on a field renders it as an embedded resource.
+/// - `#[hal(skip)]` on a field leaves it out of the document entirely.
+///
+/// In href format strings, `{}` is the value of the field itself and `{name}` is the value of
+/// another field on the struct. Link and embedded fields of type `Option<T>` are only rendered when
+/// present, and those of type `Vec<T>` are rendered once per entry.
+#[proc_macro_derive(HalResource, attributes(hal, serde))]
+pub fn derive_hal_resource(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    resource::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derive an implementation of `HalTemplate` for a struct with named fields.
+///
+/// Every field becomes a property of the template, named the same way that serde would
+/// deserialize it. Fields are required unless they are an `Option<T>` or have a serde default, and
+/// fields whose type deserializes from an enum get inline options for each of the variants.
+/// Flattened fields must themselves implement `HalTemplate`, and contribute all of their
+/// properties.
+///
+/// Supported attributes on the struct:
+/// - `#[hal_template(name = "create")]` sets the name of the template.
+/// - `#[hal_template(method = POST)]` sets the method, as either an `HttpMethod` or a string.
+/// - `#[hal_template(content_type = Form)]` sets the content type, as either a
+///   `TemplateContentType` or a string.
+/// - `#[hal_template(target = "/users", title = "Create User")]` sets the target and title.
+///
+/// Supported attributes on fields:
+/// - `#[hal_template(skip)]` leaves the field out of the template.
+/// - `#[hal_template(required)]` or `#[hal_template(required = false)]` overrides whether the
+///   property is required.
+/// - `#[hal_template(type = Email)]` sets the type, as either a `TemplatePropertyType` or a string.
+/// - `#[hal_template(regex = "...", prompt = "...", placeholder = "...")]`.
+/// - `#[hal_template(min = 1, max = 10, min_length = 1, max_length = 10)]`.
+/// - `#[hal_template(readonly)]`.
+#[proc_macro_derive(HalTemplate, attributes(hal_template, serde))]
+pub fn derive_hal_template(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    template::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
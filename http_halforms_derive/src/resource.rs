@@ -0,0 +1,328 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, DeriveInput, Field, GenericParam, Generics, Ident, LitStr};
+
+use crate::{
+    format::placeholders,
+    utils::{named_fields, serde_attrs, serde_rename_all, wrapper, Wrapper},
+};
+
+/// How a single field of the struct is rendered.
+enum FieldKind {
+    /// The field is part of the payload.
+    Payload,
+    /// The field is left out of the document.
+    Skip,
+    /// The field is rendered as a link.
+    Link { rel: LitStr, href: LitStr },
+    /// The field is rendered as an embedded resource.
+    Embedded { rel: LitStr },
+}
+
+/// Generate the `HalResource` implementation for a struct.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = named_fields(input, "HalResource")?;
+    let field_names: Vec<String> = fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+        .map(ToString::to_string)
+        .collect();
+
+    let self_href = self_href(input)?;
+
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let payload_ident = format_ident!("{}HalPayload", ident);
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // The payload only implements `Serialize` if all of its generic parameters do.
+    let mut bounded = generics.clone();
+    bounded.make_where_clause().predicates.push(parse_quote! {
+        #payload_ident #ty_generics: ::http_halforms::__private::serde::Serialize
+    });
+    let impl_where_clause = &bounded.where_clause;
+    let rename_all = serde_rename_all(&input.attrs)?;
+
+    let mut payload_fields = vec![];
+    let mut payload_names = vec![];
+    let mut links = vec![];
+    let mut embedded_names = vec![];
+    let mut embedded = vec![];
+
+    if let Some(href) = self_href {
+        let value = format_href(&href, None, &field_names)?;
+        links.push(quote! {
+            __links.push(("self", #value));
+        });
+    }
+
+    for field in &fields {
+        let name = field
+            .ident
+            .as_ref()
+            .expect("named fields always have an ident");
+
+        match field_kind(field)? {
+            FieldKind::Payload => {
+                let attrs = serde_attrs(&field.attrs);
+                let field_vis = &field.vis;
+                let ty = &field.ty;
+                payload_fields.push(quote! {
+                    #(#attrs)*
+                    #field_vis #name: #ty
+                });
+                payload_names.push(name);
+            },
+            FieldKind::Skip => {},
+            FieldKind::Link { rel, href } => {
+                links.push(link_statement(field, &rel, &href, &field_names)?);
+            },
+            FieldKind::Embedded { rel } => {
+                embedded.push(embedded_statement(field, &rel));
+                embedded_names.push(name);
+            },
+        }
+    }
+
+    let (phantom_field, phantom_value) = phantom(generics);
+
+    let payload_doc = format!("The payload of [`{ident}`] when rendered as a HAL document.");
+
+    Ok(quote! {
+        #[doc = #payload_doc]
+        #[derive(::http_halforms::__private::serde::Serialize)]
+        #[serde(crate = "::http_halforms::__private::serde")]
+        #(#rename_all)*
+        #vis struct #payload_ident #generics #where_clause {
+            #(#payload_fields,)*
+            #phantom_field
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::http_halforms::HalResource for #ident #ty_generics #impl_where_clause {
+            type Payload = #payload_ident #ty_generics;
+
+            fn into_hal(self) -> ::http_halforms::Hal<Self::Payload> {
+                #[allow(unused_mut)]
+                let mut __links: ::std::vec::Vec<(&'static str, ::std::string::String)> =
+                    ::std::vec::Vec::new();
+                #(#links)*
+
+                let Self {
+                    #(#payload_names,)*
+                    #(#embedded_names,)*
+                    ..
+                } = self;
+                let mut __hal = ::http_halforms::Hal::new(#payload_ident {
+                    #(#payload_names,)*
+                    #phantom_value
+                });
+                for (__rel, __href) in __links {
+                    __hal = __hal.with_link(__rel, __href);
+                }
+                #(#embedded)*
+
+                __hal
+            }
+        }
+
+        #[automatically_derived]
+        impl #impl_generics ::std::convert::From<#ident #ty_generics>
+            for ::http_halforms::Hal<#payload_ident #ty_generics> #impl_where_clause
+        {
+            fn from(value: #ident #ty_generics) -> Self {
+                ::http_halforms::HalResource::into_hal(value)
+            }
+        }
+    })
+}
+
+/// Generate the statement that collects the links for a link field.
+fn link_statement(
+    field: &Field,
+    rel: &LitStr,
+    href: &LitStr,
+    field_names: &[String],
+) -> syn::Result<TokenStream> {
+    let name = &field.ident;
+    let value = format_href(href, Some(quote!(__value)), field_names)?;
+
+    Ok(match wrapper(&field.ty) {
+        Wrapper::Plain => quote! {
+            let __value = &self.#name;
+            __links.push((#rel, #value));
+        },
+        Wrapper::Option => quote! {
+            if let ::std::option::Option::Some(__value) = &self.#name {
+                __links.push((#rel, #value));
+            }
+        },
+        Wrapper::Vec => quote! {
+            for __value in &self.#name {
+                __links.push((#rel, #value));
+            }
+        },
+    })
+}
+
+/// Generate the statement that adds the embedded resources for an embedded field.
+fn embedded_statement(field: &Field, rel: &LitStr) -> TokenStream {
+    let name = &field.ident;
+
+    match wrapper(&field.ty) {
+        Wrapper::Plain => quote! {
+            __hal = __hal.with_embedded(#rel, ::http_halforms::HalResource::into_hal(#name));
+        },
+        Wrapper::Option => quote! {
+            __hal = __hal.maybe_with_embedded(
+                #rel,
+                #name.map(::http_halforms::HalResource::into_hal),
+            );
+        },
+        Wrapper::Vec => quote! {
+            for __value in #name {
+                __hal = __hal.with_embedded(#rel, ::http_halforms::HalResource::into_hal(__value));
+            }
+        },
+    }
+}
+
+/// Generate the field declaration and value that use up all of the generic parameters of the
+/// payload, since some of them may only be used by links or embedded resources.
+fn phantom(generics: &Generics) -> (TokenStream, TokenStream) {
+    let types: Vec<TokenStream> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Lifetime(lifetime) => {
+                let lifetime = &lifetime.lifetime;
+                Some(quote!(&#lifetime ()))
+            },
+            GenericParam::Type(ty) => {
+                let ty = &ty.ident;
+                Some(quote!(#ty))
+            },
+            GenericParam::Const(_) => None,
+        })
+        .collect();
+
+    if types.is_empty() {
+        (quote!(), quote!())
+    } else {
+        (
+            quote! {
+                #[serde(skip)]
+                __phantom: ::std::marker::PhantomData<fn() -> (#(#types,)*)>,
+            },
+            quote!(__phantom: ::std::marker::PhantomData,),
+        )
+    }
+}
+
+/// Get the self href from the struct-level `#[hal(...)]` attributes, if there is one.
+fn self_href(input: &DeriveInput) -> syn::Result<Option<LitStr>> {
+    let mut self_href = None;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("hal"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("self_href") {
+                self_href = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported hal attribute"))
+            }
+        })?;
+    }
+
+    Ok(self_href)
+}
+
+/// Work out how a field is rendered from its `#[hal(...)]` attributes.
+fn field_kind(field: &Field) -> syn::Result<FieldKind> {
+    let mut skip = false;
+    let mut link = None;
+    let mut href = None;
+    let mut embedded = None;
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("hal"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+            } else if meta.path.is_ident("link") {
+                link = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("href") {
+                href = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("embedded") {
+                embedded = Some(meta.value()?.parse()?);
+            } else {
+                return Err(meta.error("unsupported hal attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    match (skip, link, href, embedded) {
+        (false, None, None, None) => Ok(FieldKind::Payload),
+        (true, None, None, None) => Ok(FieldKind::Skip),
+        (false, Some(rel), Some(href), None) => Ok(FieldKind::Link { rel, href }),
+        (false, None, None, Some(rel)) => Ok(FieldKind::Embedded { rel }),
+        (false, Some(_), None, None) => Err(syn::Error::new_spanned(
+            field,
+            "link fields require an `href`",
+        )),
+        (false, None, Some(_), None) => Err(syn::Error::new_spanned(
+            field,
+            "`href` can only be used on link fields",
+        )),
+        _ => Err(syn::Error::new_spanned(
+            field,
+            "a field can only be one of `skip`, `link` or `embedded`",
+        )),
+    }
+}
+
+/// Generate the expression that formats an href, with `{}` referring to `value` and `{name}`
+/// referring to the field of that name.
+fn format_href(
+    href: &LitStr,
+    value: Option<TokenStream>,
+    field_names: &[String],
+) -> syn::Result<TokenStream> {
+    let placeholders = placeholders(href)?;
+
+    let positional = match (placeholders.positional, value) {
+        (false, _) => quote!(),
+        (true, Some(value)) => quote!(#value,),
+        (true, None) => {
+            return Err(syn::Error::new_spanned(
+                href,
+                "`{}` can only be used in the href of a link field",
+            ));
+        },
+    };
+
+    let mut named = vec![];
+    for name in &placeholders.named {
+        if !field_names.contains(name) {
+            return Err(syn::Error::new_spanned(
+                href,
+                format!("unknown field `{name}` in href"),
+            ));
+        }
+        let name = Ident::new(name, href.span());
+        named.push(quote!(#name = self.#name));
+    }
+
+    Ok(quote! {
+        ::std::format!(#href, #positional #(#named,)*)
+    })
+}
@@ -0,0 +1,271 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::{format_ident, quote};
+use syn::{meta::ParseNestedMeta, parse_quote, DeriveInput, Field, Ident, LitBool, LitInt, LitStr};
+
+use crate::{
+    case::rename_field,
+    utils::{
+        inner_type, named_fields, serde_container, serde_field, wrapper, SerdeContainer, Wrapper,
+    },
+};
+
+/// The `#[hal_template(...)]` attributes on a struct.
+#[derive(Default)]
+struct TemplateSettings {
+    name:     Option<LitStr>,
+    settings: Vec<TokenStream>,
+}
+
+/// The `#[hal_template(...)]` attributes on a field.
+#[derive(Default)]
+struct PropertySettings {
+    skip:     bool,
+    required: Option<bool>,
+    settings: Vec<TokenStream>,
+}
+
+/// The generated code for a single property of the template.
+struct Property {
+    /// The statements that add the property to the template.
+    tokens:        TokenStream,
+    /// Whether the options of the property are probed from the field type, which then needs to be
+    /// deserializable.
+    probe_options: bool,
+}
+
+/// Generate the `HalTemplate` implementation for a struct.
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let fields = named_fields(input, "HalTemplate")?;
+    let container = serde_container(&input.attrs)?;
+    if let Some(rule) = &container.rename_all {
+        if rename_field(&rule.value(), "").is_none() {
+            return Err(syn::Error::new_spanned(rule, "unknown rename rule"));
+        }
+    }
+
+    let TemplateSettings { name, settings } = template_settings(input)?;
+    let name = name.map(|name| quote!(const NAME: &'static str = #name;));
+
+    let mut generics = input.generics.clone();
+    let mut properties = vec![];
+    for field in fields {
+        if let Some(property) = property(field, &container)? {
+            properties.push(property.tokens);
+
+            // Probing for enum options needs the field type to be deserializable.
+            if property.probe_options {
+                let ty = &field.ty;
+                generics.make_where_clause().predicates.push(parse_quote! {
+                    #ty: ::http_halforms::__private::serde::Deserialize<'static>
+                });
+            }
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #impl_generics ::http_halforms::HalTemplate for #ident #ty_generics #where_clause {
+            #name
+
+            fn template() -> ::http_halforms::Template {
+                let mut __template = ::http_halforms::Template::default();
+                #(#settings)*
+                #(#properties)*
+
+                __template
+            }
+        }
+    })
+}
+
+/// Generate the statements that add the property for a single field to the template, or `None`
+/// if the field isn't part of the template.
+fn property(field: &Field, container: &SerdeContainer) -> syn::Result<Option<Property>> {
+    let serde = serde_field(&field.attrs)?;
+    let PropertySettings {
+        skip,
+        required,
+        settings,
+    } = property_settings(field)?;
+    if skip || serde.skip {
+        return Ok(None);
+    }
+
+    let ty = &field.ty;
+    let optional = wrapper(ty) == Wrapper::Option || serde.default || container.default;
+
+    if serde.flatten {
+        let inner = inner_type(ty);
+        let required = optional.then(|| quote!(.with_required(false)));
+
+        return Ok(Some(Property {
+            tokens:        quote! {
+                for __property in <#inner as ::http_halforms::HalTemplate>::template().properties {
+                    __template = __template.with_property(__property #required);
+                }
+            },
+            probe_options: false,
+        }));
+    }
+
+    let name = match (&serde.rename, &container.rename_all) {
+        (Some(rename), _) => rename.value(),
+        (None, rule) => {
+            let ident = field
+                .ident
+                .as_ref()
+                .expect("named fields always have an ident")
+                .to_string();
+            let ident = ident.strip_prefix("r#").unwrap_or(&ident);
+
+            match rule {
+                Some(rule) => rename_field(&rule.value(), ident).unwrap_or_default(),
+                None => ident.to_owned(),
+            }
+        },
+    };
+    let required = required.unwrap_or(!optional).then(|| quote!(.required()));
+
+    let options = (!serde.custom).then(|| {
+        quote! {
+            let __property = match ::http_halforms::__private::enum_options::<#ty>() {
+                ::std::option::Option::Some(__options) => __property.with_options(__options),
+                ::std::option::Option::None => __property,
+            };
+        }
+    });
+
+    Ok(Some(Property {
+        probe_options: options.is_some(),
+        tokens:        quote! {
+            let __property = ::http_halforms::TemplateProperty::new(#name) #required #(#settings)*;
+            #options
+            __template = __template.with_property(__property);
+        },
+    }))
+}
+
+/// Parse the `#[hal_template(...)]` attributes on a struct.
+fn template_settings(input: &DeriveInput) -> syn::Result<TemplateSettings> {
+    let mut result = TemplateSettings::default();
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("hal_template"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                result.name = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("method") {
+                let value = string_or_variant(&meta, "HttpMethod")?;
+                result
+                    .settings
+                    .push(quote!(__template = __template.with_method(#value);));
+            } else if meta.path.is_ident("content_type") {
+                let value = string_or_variant(&meta, "TemplateContentType")?;
+                result
+                    .settings
+                    .push(quote!(__template = __template.with_content_type(#value);));
+            } else if meta.path.is_ident("target") {
+                let value: LitStr = meta.value()?.parse()?;
+                result
+                    .settings
+                    .push(quote!(__template = __template.with_target(#value);));
+            } else if meta.path.is_ident("title") {
+                let value: LitStr = meta.value()?.parse()?;
+                result
+                    .settings
+                    .push(quote!(__template = __template.with_title(#value);));
+            } else {
+                return Err(meta.error("unsupported hal_template attribute"));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(result)
+}
+
+/// Parse the `#[hal_template(...)]` attributes on a field.
+fn property_settings(field: &Field) -> syn::Result<PropertySettings> {
+    let mut result = PropertySettings::default();
+
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("hal_template"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let setting = if meta.path.is_ident("skip") {
+                result.skip = true;
+                return Ok(());
+            } else if meta.path.is_ident("required") {
+                result.required = Some(if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<LitBool>()?.value
+                } else {
+                    true
+                });
+                return Ok(());
+            } else if meta.path.is_ident("readonly") {
+                quote!(.readonly())
+            } else if meta.path.is_ident("type") {
+                let value = string_or_variant(&meta, "TemplatePropertyType")?;
+                quote!(.with_type(#value))
+            } else if meta.path.is_ident("regex") {
+                let value: LitStr = meta.value()?.parse()?;
+                quote!(.with_regex(#value))
+            } else if meta.path.is_ident("prompt") {
+                let value: LitStr = meta.value()?.parse()?;
+                quote!(.with_prompt(#value))
+            } else if meta.path.is_ident("placeholder") {
+                let value: LitStr = meta.value()?.parse()?;
+                quote!(.with_placeholder(#value))
+            } else if meta.path.is_ident("min") {
+                let value = number(&meta)?;
+                quote!(.with_min(#value))
+            } else if meta.path.is_ident("max") {
+                let value = number(&meta)?;
+                quote!(.with_max(#value))
+            } else if meta.path.is_ident("min_length") {
+                let value = number(&meta)?;
+                quote!(.with_min_length(#value))
+            } else if meta.path.is_ident("max_length") {
+                let value = number(&meta)?;
+                quote!(.with_max_length(#value))
+            } else {
+                return Err(meta.error("unsupported hal_template attribute"));
+            };
+
+            result.settings.push(setting);
+            Ok(())
+        })?;
+    }
+
+    Ok(result)
+}
+
+/// Parse a value that is either a string literal or the name of a variant of one of the enums in
+/// `http_halforms::values`.
+fn string_or_variant(meta: &ParseNestedMeta, values: &str) -> syn::Result<TokenStream> {
+    let value = meta.value()?;
+
+    if value.peek(LitStr) {
+        let value: LitStr = value.parse()?;
+        Ok(quote!(#value))
+    } else {
+        let variant: Ident = value.parse()?;
+        let values = format_ident!("{}", values);
+        Ok(quote!(::http_halforms::values::#values::#variant))
+    }
+}
+
+/// Parse a numeric value for a property.
+fn number(meta: &ParseNestedMeta) -> syn::Result<Literal> {
+    let value: LitInt = meta.value()?.parse()?;
+
+    Ok(Literal::u32_suffixed(value.base10_parse()?))
+}
@@ -0,0 +1,231 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    meta::ParseNestedMeta, Attribute, Data, DeriveInput, Field, Fields, GenericArgument, LitStr,
+    PathArguments, Token, Type,
+};
+
+/// Get the fields of a struct with named fields, or an error naming the derive if the input is
+/// anything else.
+pub fn named_fields<'a>(input: &'a DeriveInput, derive: &str) -> syn::Result<Vec<&'a Field>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            format!("{derive} can only be derived for structs"),
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            input,
+            format!("{derive} can only be derived for structs with named fields"),
+        ));
+    };
+
+    Ok(fields.named.iter().collect())
+}
+
+/// The container type wrapping a field, which determines how many times it is rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Wrapper {
+    /// The field is rendered exactly once.
+    Plain,
+    /// The field is an `Option<T>` and is rendered only if present.
+    Option,
+    /// The field is a `Vec<T>` and is rendered once per entry.
+    Vec,
+}
+
+/// Determine the wrapper of a field from its type.
+///
+/// This is purely syntactic, so only matches types written as `Option<T>` or `Vec<T>`, optionally
+/// with a leading path.
+pub fn wrapper(ty: &Type) -> Wrapper {
+    let Type::Path(path) = ty else {
+        return Wrapper::Plain;
+    };
+    if path.qself.is_some() {
+        return Wrapper::Plain;
+    }
+    let Some(segment) = path.path.segments.last() else {
+        return Wrapper::Plain;
+    };
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return Wrapper::Plain;
+    };
+    if args.args.len() != 1 || !matches!(args.args.first(), Some(GenericArgument::Type(_))) {
+        return Wrapper::Plain;
+    }
+
+    if segment.ident == "Option" {
+        Wrapper::Option
+    } else if segment.ident == "Vec" {
+        Wrapper::Vec
+    } else {
+        Wrapper::Plain
+    }
+}
+
+/// Get all of the `#[serde(...)]` attributes from a list of attributes.
+pub fn serde_attrs(attrs: &[Attribute]) -> impl Iterator<Item = &Attribute> {
+    attrs.iter().filter(|attr| attr.path().is_ident("serde"))
+}
+
+/// Get the `#[serde(rename_all = "...")]` attributes from a list of container attributes.
+///
+/// Other container attributes are dropped since they don't necessarily make sense for a generated
+/// type.
+pub fn serde_rename_all(attrs: &[Attribute]) -> syn::Result<Vec<TokenStream>> {
+    let mut result = vec![];
+
+    for attr in serde_attrs(attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                if meta.input.peek(Token![=]) {
+                    let value: LitStr = meta.value()?.parse()?;
+                    result.push(quote!(#[serde(rename_all = #value)]));
+                } else {
+                    let content;
+                    syn::parenthesized!(content in meta.input);
+                    let content: TokenStream = content.parse()?;
+                    result.push(quote!(#[serde(rename_all(#content))]));
+                }
+                Ok(())
+            } else {
+                skip_meta(&meta)
+            }
+        })?;
+    }
+
+    Ok(result)
+}
+
+/// Consume the value of a nested meta item that we're not interested in.
+pub fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<syn::Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        let content;
+        syn::parenthesized!(content in meta.input);
+        content.parse::<TokenStream>()?;
+    }
+
+    Ok(())
+}
+
+/// The serde attributes on a field that affect how it is deserialized.
+#[derive(Debug, Default)]
+#[allow(clippy::struct_excessive_bools)] // These mirror independent serde flags.
+pub struct SerdeField {
+    /// The name the field is deserialized from, if it was renamed.
+    pub rename:  Option<LitStr>,
+    /// Whether the field has a default value, and so can be missing.
+    pub default: bool,
+    /// Whether the field is never deserialized.
+    pub skip:    bool,
+    /// Whether the fields of this field are flattened into the parent.
+    pub flatten: bool,
+    /// Whether the field uses a custom deserialization function.
+    pub custom:  bool,
+}
+
+/// The serde attributes on a struct that affect how it is deserialized.
+#[derive(Debug, Default)]
+pub struct SerdeContainer {
+    /// The rule used to rename all fields when deserializing.
+    pub rename_all: Option<LitStr>,
+    /// Whether all fields have default values, and so can be missing.
+    pub default:    bool,
+}
+
+/// Parse the `#[serde(...)]` attributes on a field.
+pub fn serde_field(attrs: &[Attribute]) -> syn::Result<SerdeField> {
+    let mut result = SerdeField::default();
+
+    for attr in serde_attrs(attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                result.rename = deserialize_value(&meta)?.or(result.rename.take());
+            } else if meta.path.is_ident("default") {
+                result.default = true;
+                skip_meta(&meta)?;
+            } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+                result.skip = true;
+            } else if meta.path.is_ident("flatten") {
+                result.flatten = true;
+            } else if meta.path.is_ident("deserialize_with") || meta.path.is_ident("with") {
+                result.custom = true;
+                skip_meta(&meta)?;
+            } else {
+                skip_meta(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(result)
+}
+
+/// Parse the `#[serde(...)]` attributes on a struct.
+pub fn serde_container(attrs: &[Attribute]) -> syn::Result<SerdeContainer> {
+    let mut result = SerdeContainer::default();
+
+    for attr in serde_attrs(attrs) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename_all") {
+                result.rename_all = deserialize_value(&meta)?.or(result.rename_all.take());
+            } else if meta.path.is_ident("default") {
+                result.default = true;
+                skip_meta(&meta)?;
+            } else {
+                skip_meta(&meta)?;
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(result)
+}
+
+/// Parse the value of an attribute that is either `name = "value"` or
+/// `name(serialize = "...", deserialize = "...")`, returning the value used for deserializing.
+fn deserialize_value(meta: &ParseNestedMeta) -> syn::Result<Option<LitStr>> {
+    if meta.input.peek(Token![=]) {
+        return Ok(Some(meta.value()?.parse()?));
+    }
+
+    let mut result = None;
+    meta.parse_nested_meta(|inner| {
+        if inner.path.is_ident("deserialize") {
+            result = Some(inner.value()?.parse()?);
+        } else {
+            skip_meta(&inner)?;
+        }
+        Ok(())
+    })?;
+
+    Ok(result)
+}
+
+/// Get the type inside an `Option<T>` or `Vec<T>`, or the type itself if it is neither.
+pub fn inner_type(ty: &Type) -> &Type {
+    if wrapper(ty) == Wrapper::Plain {
+        return ty;
+    }
+
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .and_then(|segment| match &segment.arguments {
+                PathArguments::AngleBracketed(args) => args.args.first(),
+                _ => None,
+            })
+            .and_then(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .unwrap_or(ty),
+        _ => ty,
+    }
+}
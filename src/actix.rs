@@ -0,0 +1,327 @@
+mod accept;
+
+use actix_web::{
+    body::BoxBody,
+    http::{
+        header::{HeaderName, HeaderValue},
+        StatusCode,
+    },
+    HttpRequest, HttpResponse, Responder, ResponseError,
+};
+use serde::Serialize;
+
+use crate::{Error, HalResponse};
+
+impl<P> Responder for HalResponse<P>
+where
+    P: Serialize,
+{
+    type Body = BoxBody;
+
+    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let response = match self.into_http_response::<Vec<u8>>() {
+            Ok(response) => response,
+            Err(e) => return e.error_response(),
+        };
+
+        // Actix uses its own version of the `http` types, so everything needs converting across.
+        let (parts, body) = response.into_parts();
+
+        let status_code = StatusCode::from_u16(parts.status.as_u16())
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        let mut result = HttpResponse::with_body(status_code, body).map_into_boxed_body();
+
+        let headers = result.headers_mut();
+        for (header_name, header_value) in &parts.headers {
+            if let (Ok(header_name), Ok(header_value)) = (
+                HeaderName::from_bytes(header_name.as_str().as_bytes()),
+                HeaderValue::from_bytes(header_value.as_bytes()),
+            ) {
+                headers.append(header_name, header_value);
+            }
+        }
+
+        result
+    }
+}
+
+impl ResponseError for Error {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            // Cursors come from the client, so a bad one is the client's fault.
+            Self::InvalidCursor => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        HttpResponse::new(self.status_code())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use actix_web::{test, web, App};
+    use assert2::check;
+    use headers::{CacheControl, ContentType, ETag};
+    use insta::assert_json_snapshot;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::Hal;
+
+    #[actix_web::test]
+    async fn hal_example() {
+        let app = test::init_service(App::new().route(
+            "/test",
+            web::get().to(|| async {
+                crate::new(json!({
+                  "currentlyProcessing": 14,
+                  "shippedToday": 20
+                }))
+                .with_link("self", "/orders")
+                .with_link("next", "/orders?page=2")
+                .with_link("find", crate::Link::new("/orders{?id}").templated())
+                .with_embedded(
+                    "orders",
+                    crate::Hal::new(json!({
+                      "total": 30.00,
+                      "currency": "USD",
+                      "status": "shipped"
+                    }))
+                    .with_link("self", "/orders/123")
+                    .with_link("basket", "/baskets/98712")
+                    .with_link("customer", "/customers/7809"),
+                )
+                .with_embedded(
+                    "orders",
+                    crate::Hal::new(json!({
+                      "total": 20.00,
+                      "currency": "USD",
+                      "status": "processing"
+                    }))
+                    .with_link("self", "/orders/124")
+                    .with_link("basket", "/baskets/97213")
+                    .with_link("customer", "/customers/12369"),
+                )
+            }),
+        ))
+        .await;
+
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/test").to_request()).await;
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.headers().get("content-type").unwrap() == "application/hal+json");
+
+        let body: Value = test::read_body_json(response).await;
+        assert_json_snapshot!(body, @r###"
+        {
+          "_links": {
+            "find": {
+              "href": "/orders{?id}",
+              "templated": true
+            },
+            "next": {
+              "href": "/orders?page=2"
+            },
+            "self": {
+              "href": "/orders"
+            }
+          },
+          "_embedded": {
+            "orders": [
+              {
+                "_links": {
+                  "basket": {
+                    "href": "/baskets/98712"
+                  },
+                  "customer": {
+                    "href": "/customers/7809"
+                  },
+                  "self": {
+                    "href": "/orders/123"
+                  }
+                },
+                "total": 30.0,
+                "currency": "USD",
+                "status": "shipped"
+              },
+              {
+                "_links": {
+                  "basket": {
+                    "href": "/baskets/97213"
+                  },
+                  "customer": {
+                    "href": "/customers/12369"
+                  },
+                  "self": {
+                    "href": "/orders/124"
+                  }
+                },
+                "total": 20.0,
+                "currency": "USD",
+                "status": "processing"
+              }
+            ]
+          },
+          "currentlyProcessing": 14,
+          "shippedToday": 20
+        }
+        "###);
+    }
+
+    #[actix_web::test]
+    async fn with_nested_template() {
+        let app = test::init_service(App::new().route(
+            "/test",
+            web::get().to(|| async {
+                crate::new(()).with_embedded(
+                    "other",
+                    Hal::new(()).with_template("default", crate::Template::default()),
+                )
+            }),
+        ))
+        .await;
+
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/test").to_request()).await;
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.headers().get("content-type").unwrap() == "application/prs.hal-forms+json");
+
+        let body: Value = test::read_body_json(response).await;
+        assert_json_snapshot!(body, @r###"
+        {
+          "_embedded": {
+            "other": {
+              "_templates": {
+                "default": {}
+              }
+            }
+          }
+        }
+        "###);
+    }
+
+    #[actix_web::test]
+    async fn status_code() {
+        let app = test::init_service(App::new().route(
+            "/test",
+            web::get().to(|| async { crate::new(()).with_status_code(http::StatusCode::ACCEPTED) }),
+        ))
+        .await;
+
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/test").to_request()).await;
+
+        check!(response.status() == StatusCode::ACCEPTED);
+        check!(response.headers().get("content-type").unwrap() == "application/json");
+
+        let body: Value = test::read_body_json(response).await;
+        assert_json_snapshot!(body, @r###"{}"###);
+    }
+
+    #[actix_web::test]
+    async fn headers() {
+        let app = test::init_service(App::new().route(
+            "/test",
+            web::get().to(|| async {
+                crate::new(())
+                    .with_header(
+                        CacheControl::new()
+                            .with_public()
+                            .with_max_age(std::time::Duration::from_secs(3600)),
+                    )
+                    .with_header(ETag::from_str("\"Hello\"").unwrap())
+                    .with_header(ContentType::xml())
+            }),
+        ))
+        .await;
+
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/test").to_request()).await;
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.headers().get("content-type").unwrap() == "application/json");
+        check!(response.headers().get("cache-control").unwrap() == "public, max-age=3600");
+        check!(response.headers().get("etag").unwrap() == "\"Hello\"");
+
+        let body: Value = test::read_body_json(response).await;
+        assert_json_snapshot!(body, @r###"{}"###);
+    }
+
+    #[actix_web::test]
+    async fn invalid_payload() {
+        let app = test::init_service(App::new().route(
+            "/test",
+            web::get().to(|| async { crate::try_new(json!({"_links": "oops"})) }),
+        ))
+        .await;
+
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/test").to_request()).await;
+
+        check!(response.status() == StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[actix_web::test]
+    async fn invalid_cursor() {
+        let app = test::init_service(App::new().route(
+            "/test",
+            web::get().to(|| async {
+                crate::CursorQuery::from_query("cursor=tampered")
+                    .decode::<u64>(&crate::CursorCodec::default())
+                    .map(|_| crate::new(()))
+            }),
+        ))
+        .await;
+
+        let response =
+            test::call_service(&app, test::TestRequest::get().uri("/test").to_request()).await;
+
+        check!(response.status() == StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn negotiate() {
+        let app = test::init_service(App::new().route(
+            "/test",
+            web::get().to(|accept: crate::Accept| async move {
+                crate::new(json!({"name": "Graham"}))
+                    .with_link("self", "/test")
+                    .with_accept(accept)
+            }),
+        ))
+        .await;
+
+        let response = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/test")
+                .insert_header(("accept", "application/json"))
+                .to_request(),
+        )
+        .await;
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.headers().get("content-type").unwrap() == "application/json");
+        check!(response.headers().get("vary").unwrap() == "accept");
+
+        let body: Value = test::read_body_json(response).await;
+        check!(body == json!({"name": "Graham"}));
+
+        let response = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/test")
+                .insert_header(("accept", "image/png"))
+                .to_request(),
+        )
+        .await;
+
+        check!(response.status() == StatusCode::NOT_ACCEPTABLE);
+    }
+}
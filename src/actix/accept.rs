@@ -0,0 +1,24 @@
+use std::{
+    convert::Infallible,
+    future::{ready, Ready},
+};
+
+use actix_web::{dev::Payload, http::header::ACCEPT, FromRequest, HttpRequest};
+
+use crate::Accept;
+
+impl FromRequest for Accept {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let header = req
+            .headers()
+            .get_all(ACCEPT)
+            .filter_map(|value| value.to_str().ok())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        ready(Ok(header.parse().unwrap_or_default()))
+    }
+}
@@ -1,60 +1,72 @@
+mod accept;
+mod cursor;
+mod hal;
+mod negotiate;
+mod template_form;
+
 use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
-    Json,
 };
+pub use hal::*;
+pub use negotiate::*;
+use serde::Serialize;
+pub use template_form::*;
+
+use crate::{Error, HalResponse};
+
+impl<P> IntoResponse for HalResponse<P>
+where
+    P: Serialize,
+{
+    /// Convert the response, negotiating its representation if [`HalResponse::with_accept`] was
+    /// used or the [`negotiate`] middleware is in use.
+    ///
+    /// Otherwise the `Accept` header of the request is ignored entirely, and the response is
+    /// always returned in the richest representation that the document needs.
+    fn into_response(mut self) -> Response {
+        if self.accept.is_none() {
+            self.accept = negotiate::ACCEPT.try_with(Clone::clone).ok();
+        }
 
-use crate::{Hal, HalResponse};
+        match self.into_http_response::<Body>() {
+            Ok(response) => response,
+            Err(e) => e.into_response(),
+        }
+    }
+}
 
-impl IntoResponse for HalResponse {
+impl IntoResponse for Error {
     fn into_response(self) -> Response {
-        let status_code = self.status_code;
-
-        let content_type = if has_templates(&self.hal) {
-            "application/prs.hal-forms+json"
-        } else if !self.hal.links.is_empty() | !self.hal.embedded.is_empty() {
-            "application/hal+json"
-        } else {
-            "application/json"
-        };
-
-        let body = Json(self.hal);
-        let mut response = (status_code, body).into_response();
-
-        let headers = response.headers_mut();
-
-        for (header_name, header_value) in self.headers {
-            if let Some(header_name) = header_name {
-                headers.append(header_name, header_value);
-            }
+        match self {
+            // Cursors come from the client, so a bad one is the client's fault.
+            Self::InvalidCursor => StatusCode::BAD_REQUEST.into_response(),
+            _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
         }
-
-        response
-            .headers_mut()
-            .insert("content-type", content_type.parse().unwrap());
-        response
     }
 }
 
-/// Helper to see if the HAL document or any documented embedded into it have any templated values.
-fn has_templates(hal: &Hal) -> bool {
-    if hal.templates.is_empty() {
-        hal.embedded
-            .iter()
-            .flat_map(|e| e.1.iter())
-            .any(has_templates)
-    } else {
-        true
-    }
+/// Helper to get the media type from the `Content-Type` header of a request, without any
+/// parameters.
+fn content_type(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(str::trim)
 }
 
 #[cfg(test)]
 mod tests {
     use std::str::FromStr;
 
-    use assert2::check;
+    use assert2::{check, let_assert};
     use headers::{CacheControl, ContentType, ETag};
     use http::StatusCode;
     use insta::assert_json_snapshot;
+    #[cfg(feature = "xml")]
+    use insta::assert_snapshot;
     use serde_json::{json, Value};
 
     use crate::Hal;
@@ -279,4 +291,383 @@ mod tests {
         let body: Value = response.json();
         assert_json_snapshot!(body, @r###"{}"###);
     }
+
+    #[tokio::test]
+    async fn link_headers() {
+        let router: axum::Router = axum::Router::new().route(
+            "/test",
+            axum::routing::get(|| async {
+                crate::new(())
+                    .with_link("self", "/test")
+                    .with_link("next", "/test?page=2")
+                    .with_header(ETag::from_str("\"Hello\"").unwrap())
+                    .with_link_header("next")
+            }),
+        );
+
+        let test_server = axum_test::TestServer::new(router).unwrap();
+
+        let response = test_server.get("/test").await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.header("ETag") == "\"Hello\"");
+        check!(response.header("Link") == "</test?page=2>; rel=\"next\"");
+    }
+
+    #[tokio::test]
+    async fn link_templates_on_head() {
+        let router: axum::Router = axum::Router::new().route(
+            "/users",
+            axum::routing::get(|| async {
+                crate::new(())
+                    .with_link("self", "/users")
+                    .with_link("search", crate::Link::new("/users{?q}").templated())
+                    .with_link_templates()
+            }),
+        );
+
+        let test_server = axum_test::TestServer::new(router).unwrap();
+
+        let response = test_server.method(http::Method::HEAD, "/users").await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.as_bytes().is_empty());
+
+        let header = response.header("Link-Template");
+        let_assert!(Ok(links) = crate::parse_link_template_header(header.to_str().unwrap()));
+        check!(
+            links
+                == [(
+                    "search".to_string(),
+                    crate::Link::new("/users{?q}").templated()
+                )]
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_payload() {
+        let router: axum::Router = axum::Router::new().route(
+            "/test",
+            axum::routing::get(|| async { crate::try_new(json!({"_links": "oops"})) }),
+        );
+
+        let test_server = axum_test::TestServer::new(router).unwrap();
+
+        let response = test_server.get("/test").expect_failure().await;
+
+        check!(response.status_code() == StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    fn negotiating_server() -> axum_test::TestServer {
+        let router: axum::Router = axum::Router::new().route(
+            "/test",
+            axum::routing::get(|accept: crate::Accept| async move {
+                crate::new(json!({"name": "Graham"}))
+                    .with_link("self", "/test")
+                    .with_template("default", crate::Template::default().with_method("PUT"))
+                    .with_embedded(
+                        "child",
+                        Hal::new(json!({"name": "Child"}))
+                            .with_template("delete", crate::Template::default()),
+                    )
+                    .with_accept(accept)
+            }),
+        );
+
+        axum_test::TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn negotiate_without_accept() {
+        let response = negotiating_server().get("/test").await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.header("Content-Type") == "application/prs.hal-forms+json");
+        check!(response.header("Vary") == "accept");
+
+        let body: Value = response.json();
+        check!(body["_templates"]["default"]["method"] == "PUT");
+    }
+
+    #[tokio::test]
+    async fn negotiate_hal() {
+        let response = negotiating_server()
+            .get("/test")
+            .add_header("accept", "application/hal+json, application/json;q=0.5")
+            .await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.header("Content-Type") == "application/hal+json");
+        check!(response.header("Vary") == "accept");
+
+        let body: Value = response.json();
+        assert_json_snapshot!(body, @r###"
+        {
+          "_links": {
+            "self": {
+              "href": "/test"
+            }
+          },
+          "_embedded": {
+            "child": {
+              "name": "Child"
+            }
+          },
+          "name": "Graham"
+        }
+        "###);
+    }
+
+    #[tokio::test]
+    async fn negotiate_json() {
+        let response = negotiating_server()
+            .get("/test")
+            .add_header("accept", "application/json")
+            .await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.header("Content-Type") == "application/json");
+        check!(response.header("Vary") == "accept");
+
+        let body: Value = response.json();
+        assert_json_snapshot!(body, @r###"
+        {
+          "name": "Graham"
+        }
+        "###);
+    }
+
+    #[tokio::test]
+    async fn negotiate_html() {
+        let response = negotiating_server()
+            .get("/test")
+            .add_header(
+                "accept",
+                "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+            )
+            .await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.header("Content-Type") == "text/html; charset=utf-8");
+        check!(response.header("Vary") == "accept");
+
+        let body = response.text();
+        check!(body.contains("<a href=\"/test\">/test</a>"));
+        check!(body.contains("<form class=\"template\" name=\"default\" method=\"post\" action=\"/test\" data-method=\"PUT\""));
+        check!(body.contains("<section class=\"embedded\">\n<h2>child</h2>"));
+    }
+
+    #[tokio::test]
+    async fn negotiate_siren() {
+        let response = negotiating_server()
+            .get("/test")
+            .add_header("accept", "application/vnd.siren+json")
+            .await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.header("Content-Type") == "application/vnd.siren+json");
+        check!(response.header("Vary") == "accept");
+
+        let body: Value = response.json();
+        assert_json_snapshot!(body, @r###"
+        {
+          "properties": {
+            "name": "Graham"
+          },
+          "entities": [
+            {
+              "rel": [
+                "child"
+              ],
+              "properties": {
+                "name": "Child"
+              }
+            }
+          ],
+          "links": [
+            {
+              "rel": [
+                "self"
+              ],
+              "href": "/test"
+            }
+          ],
+          "actions": [
+            {
+              "name": "default",
+              "method": "PUT",
+              "href": "/test",
+              "type": "application/json"
+            }
+          ]
+        }
+        "###);
+    }
+
+    #[cfg(feature = "xml")]
+    #[tokio::test]
+    async fn negotiate_xml() {
+        let response = negotiating_server()
+            .get("/test")
+            .add_header("accept", "application/hal+xml")
+            .await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.header("Content-Type") == "application/hal+xml");
+        check!(response.header("Vary") == "accept");
+
+        assert_snapshot!(response.text(), @r###"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <resource href="/test">
+          <resource rel="child">
+            <template name="delete"/>
+            <name>Child</name>
+          </resource>
+          <template name="default" method="PUT"/>
+          <name>Graham</name>
+        </resource>
+        "###);
+    }
+
+    #[tokio::test]
+    async fn negotiate_not_acceptable() {
+        let response = negotiating_server()
+            .get("/test")
+            .add_header("accept", "image/png")
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::NOT_ACCEPTABLE);
+        check!(response.header("Vary") == "accept");
+    }
+
+    fn negotiating_middleware_server() -> axum_test::TestServer {
+        let router: axum::Router = axum::Router::new()
+            .route(
+                "/test",
+                axum::routing::get(|| async {
+                    crate::new(json!({"name": "Graham"}))
+                        .with_link("self", "/test")
+                        .with_template("default", crate::Template::default().with_method("PUT"))
+                }),
+            )
+            .route(
+                "/explicit",
+                axum::routing::get(|| async {
+                    crate::new(json!({"name": "Graham"}))
+                        .with_link("self", "/explicit")
+                        .with_accept(crate::Accept::default())
+                }),
+            )
+            .layer(axum::middleware::from_fn(crate::negotiate));
+
+        axum_test::TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn negotiate_middleware() {
+        let server = negotiating_middleware_server();
+
+        let response = server.get("/test").await;
+        check!(response.header("Content-Type") == "application/prs.hal-forms+json");
+        check!(response.header("Vary") == "accept");
+
+        let response = server
+            .get("/test")
+            .add_header("accept", "application/hal+json")
+            .await;
+        check!(response.header("Content-Type") == "application/hal+json");
+        check!(response.header("Vary") == "accept");
+        let body: Value = response.json();
+        check!(body.get("_templates") == None);
+
+        let response = server
+            .get("/test")
+            .add_header("accept", "application/json")
+            .await;
+        check!(response.header("Content-Type") == "application/json");
+        let body: Value = response.json();
+        check!(body == json!({"name": "Graham"}));
+
+        let response = server
+            .get("/test")
+            .add_header("accept", "image/png")
+            .expect_failure()
+            .await;
+        check!(response.status_code() == StatusCode::NOT_ACCEPTABLE);
+        check!(response.header("Vary") == "accept");
+    }
+
+    #[tokio::test]
+    async fn negotiate_middleware_explicit_accept() {
+        let response = negotiating_middleware_server()
+            .get("/explicit")
+            .add_header("accept", "application/json")
+            .await;
+
+        check!(response.header("Content-Type") == "application/hal+json");
+    }
+
+    #[tokio::test]
+    async fn without_negotiate_middleware() {
+        let router: axum::Router = axum::Router::new().route(
+            "/test",
+            axum::routing::get(|| async { crate::new(json!({})).with_link("self", "/test") }),
+        );
+        let response = axum_test::TestServer::new(router)
+            .unwrap()
+            .get("/test")
+            .add_header("accept", "image/png")
+            .await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.header("Content-Type") == "application/hal+json");
+        check!(response.maybe_header("Vary") == None);
+    }
+
+    fn cursor_server() -> axum_test::TestServer {
+        let router: axum::Router = axum::Router::new().route(
+            "/events",
+            axum::routing::get(|query: crate::CursorQuery| async move {
+                let codec = crate::CursorCodec::default().with_signing_key("secret");
+                let after = query.decode::<u32>(&codec)?.unwrap_or_default();
+
+                let events = (after + 1..=after + 2).map(|id| Hal::new(json!({"id": id})));
+                let page = crate::CursorPage::new("/events{?cursor}", events)
+                    .with_items_rel("events")
+                    .maybe_with_cursor(query.cursor)
+                    .with_next(codec.encode(&(after + 2))?);
+
+                Ok::<_, crate::Error>(crate::HalResponse {
+                    hal: page.into_hal()?,
+                    ..crate::new(())
+                })
+            }),
+        );
+
+        axum_test::TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn cursor_pagination() {
+        let server = cursor_server();
+
+        let first: Value = server.get("/events").await.json();
+        check!(first["_embedded"]["events"][0]["id"] == 1);
+
+        let_assert!(Some(next) = first["_links"]["next"]["href"].as_str());
+        let second: Value = server.get(next).await.json();
+        check!(second["_embedded"]["events"][0]["id"] == 3);
+        check!(second["_links"]["self"]["href"] == next);
+    }
+
+    #[tokio::test]
+    async fn invalid_cursor() {
+        let response = cursor_server()
+            .get("/events?cursor=NDI")
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::BAD_REQUEST);
+    }
 }
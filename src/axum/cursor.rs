@@ -0,0 +1,16 @@
+use std::convert::Infallible;
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+
+use crate::CursorQuery;
+
+impl<S> FromRequestParts<S> for CursorQuery
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_query(parts.uri.query().unwrap_or_default()))
+    }
+}
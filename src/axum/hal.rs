@@ -0,0 +1,155 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+
+use super::content_type;
+use crate::{Hal, Representation};
+
+/// Rejection used when a [`Hal`] document can not be extracted from a request.
+#[derive(Debug)]
+pub enum HalRejection {
+    /// The request was not `application/hal+json` or `application/json`.
+    UnsupportedMediaType,
+
+    /// The request body could not be read or parsed as a HAL document with the expected payload.
+    InvalidBody(String),
+}
+
+impl<S, P> FromRequest<S> for Hal<P>
+where
+    S: Send + Sync,
+    P: DeserializeOwned,
+{
+    type Rejection = HalRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let supported = content_type(req.headers()).is_some_and(|actual| {
+            [Representation::Hal, Representation::Json]
+                .iter()
+                .any(|expected| actual.eq_ignore_ascii_case(expected.media_type()))
+        });
+        if !supported {
+            return Err(HalRejection::UnsupportedMediaType);
+        }
+
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| HalRejection::InvalidBody(e.body_text()))?;
+
+        serde_json::from_slice(&body).map_err(|e| HalRejection::InvalidBody(e.to_string()))
+    }
+}
+
+impl IntoResponse for HalRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response(),
+            Self::InvalidBody(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::check;
+    use http::StatusCode;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct Callback {
+        status: String,
+    }
+
+    fn server() -> axum_test::TestServer {
+        let router: axum::Router = axum::Router::new().route(
+            "/callback",
+            axum::routing::post(|hal: Hal<Callback>| async move {
+                let links = hal.links.keys().cloned().collect::<Vec<_>>().join(",");
+                let items = hal
+                    .embedded
+                    .get("items")
+                    .map_or(0, |items| items.iter().count());
+
+                format!("{}:{links}:{items}", hal.payload.status)
+            }),
+        );
+
+        axum_test::TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn hal_body() {
+        let response = server()
+            .post("/callback")
+            .text(
+                json!({
+                    "_links": {
+                        "self": {"href": "/jobs/1"},
+                        "result": {"href": "/jobs/1/result"}
+                    },
+                    "_embedded": {
+                        "items": [{"id": 1}, {"id": 2}]
+                    },
+                    "status": "done"
+                })
+                .to_string(),
+            )
+            .content_type("application/hal+json")
+            .await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.text() == "done:result,self:2");
+    }
+
+    #[tokio::test]
+    async fn json_body() {
+        let response = server()
+            .post("/callback")
+            .json(&json!({"status": "pending"}))
+            .await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.text() == "pending::0");
+    }
+
+    #[tokio::test]
+    async fn wrong_content_type() {
+        let response = server()
+            .post("/callback")
+            .form(&[("status", "done")])
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn malformed_body() {
+        let response = server()
+            .post("/callback")
+            .text("{")
+            .content_type("application/hal+json")
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn invalid_payload() {
+        let response = server()
+            .post("/callback")
+            .json(&json!({"_links": {"self": {"href": "/jobs/1"}}}))
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,21 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+
+use crate::Accept;
+
+tokio::task_local! {
+    /// The media ranges accepted by the client of the request that is being handled, when the
+    /// [`negotiate`] middleware is in use.
+    pub(super) static ACCEPT: Accept;
+}
+
+/// Middleware that negotiates the representation of every [`crate::HalResponse`] returned by the
+/// handlers it wraps, using the `Accept` header of the request.
+///
+/// This is the same as every handler extracting [`Accept`] and calling
+/// [`crate::HalResponse::with_accept`] with it, and any handler that does so anyway takes
+/// precedence. Use it with `axum::middleware::from_fn(http_halforms::negotiate)`.
+pub async fn negotiate(request: Request, next: Next) -> Response {
+    let accept = Accept::from_headers(request.headers());
+
+    ACCEPT.scope(accept, next.run(request)).await
+}
@@ -0,0 +1,378 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::content_type;
+use crate::{
+    values::TemplateContentType, Error, HalTemplate, Template, ValidationErrors, ValidationFailure,
+};
+
+/// Axum extractor that validates the request body against the HAL-FORMS template for a type, and
+/// then deserializes it into that type.
+///
+/// The body is parsed as either JSON or `application/x-www-form-urlencoded` depending on the
+/// `contentType` of the template, defaulting to JSON as per the HAL-FORMS specification. Form
+/// fields that are repeated, such as `tag=a&tag=b`, are parsed as multiple values, and so can be
+/// deserialized into a `Vec`.
+#[derive(Debug)]
+pub struct TemplateForm<T>(pub T);
+
+/// Rejection used when a [`TemplateForm`] can not be extracted.
+#[derive(Debug)]
+pub enum TemplateFormRejection {
+    /// The request did not have the content type required by the template.
+    UnsupportedMediaType,
+
+    /// The request body could not be read or parsed.
+    InvalidBody(String),
+
+    /// The request body failed validation against the template.
+    Validation {
+        name:     &'static str,
+        template: Template,
+        errors:   ValidationErrors,
+    },
+
+    /// The request body passed validation but could not be deserialized into the target type.
+    Unprocessable(String),
+
+    /// The template can't be used to validate the request body, such as when a property has an
+    /// invalid regex.
+    InvalidTemplate(Error),
+}
+
+impl<S, T> FromRequest<S> for TemplateForm<T>
+where
+    S: Send + Sync,
+    T: HalTemplate + DeserializeOwned,
+{
+    type Rejection = TemplateFormRejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let template = T::template();
+
+        let expected = template
+            .content_type
+            .clone()
+            .unwrap_or_else(|| TemplateContentType::Json.to_string());
+        let is_form = expected == TemplateContentType::Form.to_string();
+
+        let actual = content_type(req.headers());
+        if !actual.is_some_and(|actual| actual.eq_ignore_ascii_case(&expected)) {
+            return Err(TemplateFormRejection::UnsupportedMediaType);
+        }
+
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| TemplateFormRejection::InvalidBody(e.body_text()))?;
+
+        let validated = if is_form {
+            template.validate_form(&body)
+        } else {
+            let value: Value = serde_json::from_slice(&body)
+                .map_err(|e| TemplateFormRejection::InvalidBody(e.to_string()))?;
+            template.validate(&value)
+        };
+
+        match validated {
+            Ok(()) => {},
+            Err(ValidationFailure::Invalid(errors)) => {
+                return Err(TemplateFormRejection::Validation {
+                    name: T::NAME,
+                    template,
+                    errors,
+                });
+            },
+            Err(ValidationFailure::Template(e)) => {
+                return Err(TemplateFormRejection::InvalidTemplate(e));
+            },
+        }
+
+        let value = if is_form {
+            serde_html_form::from_bytes(&body)
+                .map_err(|e| TemplateFormRejection::Unprocessable(e.to_string()))?
+        } else {
+            serde_json::from_slice(&body)
+                .map_err(|e| TemplateFormRejection::Unprocessable(e.to_string()))?
+        };
+
+        Ok(Self(value))
+    }
+}
+
+impl IntoResponse for TemplateFormRejection {
+    fn into_response(self) -> Response {
+        match self {
+            Self::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response(),
+            Self::InvalidBody(message) => (StatusCode::BAD_REQUEST, message).into_response(),
+            Self::Validation {
+                name,
+                template,
+                errors,
+            } => crate::new(errors)
+                .with_template(name, template)
+                .with_status_code(StatusCode::UNPROCESSABLE_ENTITY)
+                .into_response(),
+            Self::Unprocessable(message) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, message).into_response()
+            },
+            Self::InvalidTemplate(e) => e.into_response(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::check;
+    use http::StatusCode;
+    use insta::assert_json_snapshot;
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{values::TemplateContentType, InlineOption, TemplateOptions, TemplateProperty};
+
+    #[derive(Debug, Deserialize)]
+    struct CreateUser {
+        name: String,
+        age:  Option<u32>,
+    }
+
+    impl HalTemplate for CreateUser {
+        const NAME: &'static str = "create";
+
+        fn template() -> Template {
+            Template::default()
+                .with_method("POST")
+                .with_property(
+                    TemplateProperty::new("name")
+                        .required()
+                        .with_max_length(10u32),
+                )
+                .with_property(
+                    TemplateProperty::new("age")
+                        .with_type("number")
+                        .with_min(18u32),
+                )
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Search {
+        q: String,
+    }
+
+    impl HalTemplate for Search {
+        fn template() -> Template {
+            Template::default()
+                .with_content_type(TemplateContentType::Form)
+                .with_property(TemplateProperty::new("q").required())
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Lookup {
+        #[allow(dead_code)]
+        code: String,
+    }
+
+    impl HalTemplate for Lookup {
+        fn template() -> Template {
+            Template::default().with_property(TemplateProperty::new("code").with_regex("(["))
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Tag {
+        tag: Vec<String>,
+    }
+
+    impl HalTemplate for Tag {
+        fn template() -> Template {
+            Template::default()
+                .with_content_type(TemplateContentType::Form)
+                .with_property(
+                    TemplateProperty::new("tag").with_options(
+                        TemplateOptions::inline(vec![
+                            InlineOption::new("a"),
+                            InlineOption::new("b"),
+                            InlineOption::new("c"),
+                        ])
+                        .with_min_items(1u32)
+                        .with_max_items(2u32),
+                    ),
+                )
+        }
+    }
+
+    fn server() -> axum_test::TestServer {
+        let router: axum::Router = axum::Router::new()
+            .route(
+                "/users",
+                axum::routing::post(|TemplateForm(user): TemplateForm<CreateUser>| async move {
+                    format!("{}:{:?}", user.name, user.age)
+                }),
+            )
+            .route(
+                "/search",
+                axum::routing::post(|TemplateForm(search): TemplateForm<Search>| async move {
+                    search.q
+                }),
+            )
+            .route(
+                "/tags",
+                axum::routing::post(|TemplateForm(tags): TemplateForm<Tag>| async move {
+                    tags.tag.join(",")
+                }),
+            )
+            .route(
+                "/lookup",
+                axum::routing::post(|TemplateForm(_): TemplateForm<Lookup>| async move {}),
+            );
+
+        axum_test::TestServer::new(router).unwrap()
+    }
+
+    #[tokio::test]
+    async fn valid_json() {
+        let response = server()
+            .post("/users")
+            .json(&json!({"name": "Graham", "age": 30}))
+            .await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.text() == "Graham:Some(30)");
+    }
+
+    #[tokio::test]
+    async fn invalid_json() {
+        let response = server()
+            .post("/users")
+            .json(&json!({"age": 12}))
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::UNPROCESSABLE_ENTITY);
+        check!(response.header("Content-Type") == "application/prs.hal-forms+json");
+
+        let body: Value = response.json();
+        assert_json_snapshot!(body, @r###"
+        {
+          "_templates": {
+            "create": {
+              "method": "POST",
+              "properties": [
+                {
+                  "name": "name",
+                  "required": true,
+                  "maxLength": 10
+                },
+                {
+                  "name": "age",
+                  "min": 18,
+                  "type": "number"
+                }
+              ]
+            }
+          },
+          "properties": {
+            "age": [
+              {
+                "violation": "min",
+                "min": 18
+              }
+            ],
+            "name": [
+              {
+                "violation": "required"
+              }
+            ]
+          }
+        }
+        "###);
+    }
+
+    #[tokio::test]
+    async fn malformed_json() {
+        let response = server()
+            .post("/users")
+            .text("{")
+            .content_type("application/json")
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn wrong_content_type() {
+        let response = server()
+            .post("/users")
+            .form(&[("name", "Graham")])
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn valid_form() {
+        let response = server().post("/search").form(&[("q", "hal")]).await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.text() == "hal");
+    }
+
+    #[tokio::test]
+    async fn invalid_form() {
+        let response = server()
+            .post("/search")
+            .form(&[("other", "hal")])
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body: Value = response.json();
+        check!(body["properties"]["q"] == json!([{"violation": "required"}]));
+    }
+
+    #[tokio::test]
+    async fn repeated_form_fields() {
+        let response = server()
+            .post("/tags")
+            .text("tag=a&tag=b")
+            .content_type("application/x-www-form-urlencoded")
+            .await;
+
+        check!(response.status_code() == StatusCode::OK);
+        check!(response.text() == "a,b");
+
+        let response = server()
+            .post("/tags")
+            .text("tag=a&tag=b&tag=c")
+            .content_type("application/x-www-form-urlencoded")
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::UNPROCESSABLE_ENTITY);
+        let body: Value = response.json();
+        check!(body["properties"]["tag"] == json!([{"violation": "maxItems", "maxItems": 2}]));
+    }
+
+    #[tokio::test]
+    async fn invalid_template() {
+        let response = server()
+            .post("/lookup")
+            .json(&json!({"code": "ABC"}))
+            .expect_failure()
+            .await;
+
+        check!(response.status_code() == StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,440 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    hal::curie::CURIES_REL, utils::single_multiple::SingleOrMultiple, Error, Hal, Link, UriTemplate,
+};
+
+/// The names of the templates used as the template of a collection, in order of preference.
+const TEMPLATE_NAMES: [&str; 2] = ["create", "default"];
+
+/// A Collection+JSON document.
+#[derive(Serialize)]
+struct Document {
+    collection: Collection,
+}
+
+#[derive(Serialize)]
+struct Collection {
+    version: &'static str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    href: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    links: Vec<CollectionLink>,
+
+    items: Vec<Item>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    queries: Vec<Query>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    template: Option<DataTemplate>,
+}
+
+#[derive(Serialize)]
+struct CollectionLink {
+    rel: String,
+
+    href: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Item {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    href: Option<String>,
+
+    data: Vec<Data>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    links: Vec<CollectionLink>,
+}
+
+#[derive(Serialize)]
+struct Query {
+    rel: String,
+
+    href: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<String>,
+
+    data: Vec<Data>,
+}
+
+#[derive(Serialize)]
+struct DataTemplate {
+    data: Vec<Data>,
+}
+
+#[derive(Serialize)]
+struct Data {
+    name: String,
+
+    value: Value,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prompt: Option<String>,
+}
+
+impl<P> Hal<P>
+where
+    P: Serialize,
+{
+    /// Render the HAL document as a Collection+JSON document, with the documents embedded under
+    /// the given link relation as the items.
+    ///
+    /// The payload of each item becomes its `data`, with any nested objects and arrays flattened
+    /// into dotted names such as `address.street`, and its links become its `links`. Templated
+    /// links on the collection that only have query parameters become `queries`, and the `create`
+    /// template - or the `default` one if there isn't one - becomes the `template`. Anything else
+    /// on the collection, such as its payload, has no equivalent in Collection+JSON and is left
+    /// out.
+    ///
+    /// # Errors
+    /// If the payload of any item can't be serialized into a JSON object, or if any templated link
+    /// isn't a valid URI Template.
+    pub fn to_collection_json(&self, items: &str) -> Result<Value, Error> {
+        let mut queries = vec![];
+        for (rel, link) in all_links(&self.links).filter(|(_, link)| link.templated) {
+            let template = UriTemplate::parse(&link.href)?;
+            if !template.is_query() {
+                continue;
+            }
+
+            queries.push(Query {
+                rel:    rel.to_string(),
+                href:   template.expand(&Map::new())?,
+                name:   link.name.clone(),
+                prompt: link.title.clone(),
+                data:   template
+                    .variables()
+                    .into_iter()
+                    .map(|name| Data {
+                        name:   name.to_string(),
+                        value:  Value::from(""),
+                        prompt: None,
+                    })
+                    .collect(),
+            });
+        }
+
+        let template = TEMPLATE_NAMES
+            .iter()
+            .find_map(|name| self.templates.get(*name))
+            .map(|template| DataTemplate {
+                data: template
+                    .properties
+                    .iter()
+                    .map(|property| Data {
+                        name:   property.name.clone(),
+                        value:  Value::from(property.value.clone().unwrap_or_default()),
+                        prompt: property.prompt.clone(),
+                    })
+                    .collect(),
+            });
+
+        let items = self
+            .embedded
+            .get(items)
+            .iter()
+            .flat_map(|items| items.iter())
+            .map(item)
+            .collect::<Result<_, _>>()?;
+
+        let document = Document {
+            collection: Collection {
+                version: "1.0",
+                href: self_href(&self.links),
+                links: links(&self.links),
+                items,
+                queries,
+                template,
+            },
+        };
+
+        serde_json::to_value(document).map_err(Error::Serialization)
+    }
+}
+
+/// Build a single item from an embedded document.
+fn item<P>(hal: &Hal<P>) -> Result<Item, Error>
+where
+    P: Serialize,
+{
+    let mut data = vec![];
+    match serde_json::to_value(&hal.payload).map_err(Error::Serialization)? {
+        Value::Null => {},
+        Value::Object(payload) => {
+            for (name, value) in payload {
+                flatten(&mut data, name, value);
+            }
+        },
+        _ => return Err(Error::NotAnObject),
+    }
+
+    Ok(Item {
+        href: self_href(&hal.links),
+        data,
+        links: links(&hal.links),
+    })
+}
+
+/// Add the data for a single payload field to an item.
+///
+/// Collection+JSON values can only be strings, numbers, booleans or null, so objects and arrays
+/// are flattened into one entry for each value inside them, named with the path to that value -
+/// such as `address.street` or `tags.0`. Empty objects and arrays become null.
+fn flatten(data: &mut Vec<Data>, name: String, value: Value) {
+    let children = match value {
+        Value::Object(fields) if !fields.is_empty() => fields.into_iter().collect::<Vec<_>>(),
+        Value::Array(values) if !values.is_empty() => values
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| (index.to_string(), value))
+            .collect(),
+        Value::Object(_) | Value::Array(_) => {
+            data.push(Data {
+                name,
+                value: Value::Null,
+                prompt: None,
+            });
+            return;
+        },
+        value => {
+            data.push(Data {
+                name,
+                value,
+                prompt: None,
+            });
+            return;
+        },
+    };
+
+    for (key, value) in children {
+        flatten(data, format!("{name}.{key}"), value);
+    }
+}
+
+/// Iterate over every link, along with its relation, except for CURIEs.
+fn all_links(
+    links: &BTreeMap<String, SingleOrMultiple<Link>>,
+) -> impl Iterator<Item = (&str, &Link)> {
+    links
+        .iter()
+        .filter(|(rel, _)| *rel != CURIES_REL)
+        .flat_map(|(rel, links)| links.iter().map(move |link| (rel.as_str(), link)))
+}
+
+/// The href of the first self link.
+fn self_href(links: &BTreeMap<String, SingleOrMultiple<Link>>) -> Option<String> {
+    links
+        .get("self")
+        .and_then(|links| links.iter().next())
+        .map(|link| link.href.clone())
+}
+
+/// Build the links of a collection or item, from every link except the self link and any
+/// templated links.
+fn links(links: &BTreeMap<String, SingleOrMultiple<Link>>) -> Vec<CollectionLink> {
+    all_links(links)
+        .filter(|(rel, link)| *rel != "self" && !link.templated)
+        .map(|(rel, link)| CollectionLink {
+            rel:    rel.to_string(),
+            href:   link.href.clone(),
+            name:   link.name.clone(),
+            prompt: link.title.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::let_assert;
+    use insta::assert_json_snapshot;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{Template, TemplateProperty};
+
+    #[test]
+    fn collection() {
+        let hal = Hal::new(json!({"total": 2}))
+            .with_link("self", "/users")
+            .with_link("next", Link::new("/users?page=2").with_title("Next page"))
+            .with_link(
+                "search",
+                Link::new("/users{?q,page}")
+                    .templated()
+                    .with_title("Search"),
+            )
+            .with_link("user", Link::new("/users/{id}").templated())
+            .with_embedded(
+                "items",
+                Hal::new(json!({"name": "Graham", "age": 42}))
+                    .with_link("self", "/users/1")
+                    .with_link("friends", "/users/1/friends"),
+            )
+            .with_template(
+                "default",
+                Template::default().with_property(TemplateProperty::new("ignored")),
+            )
+            .with_template(
+                "create",
+                Template::default()
+                    .with_property(TemplateProperty::new("name").with_prompt("Name"))
+                    .with_property(TemplateProperty::new("age").with_value("18")),
+            );
+
+        let_assert!(Ok(collection) = hal.to_collection_json("items"));
+
+        assert_json_snapshot!(collection, @r###"
+        {
+          "collection": {
+            "version": "1.0",
+            "href": "/users",
+            "links": [
+              {
+                "rel": "next",
+                "href": "/users?page=2",
+                "prompt": "Next page"
+              }
+            ],
+            "items": [
+              {
+                "href": "/users/1",
+                "data": [
+                  {
+                    "name": "name",
+                    "value": "Graham"
+                  },
+                  {
+                    "name": "age",
+                    "value": 42
+                  }
+                ],
+                "links": [
+                  {
+                    "rel": "friends",
+                    "href": "/users/1/friends"
+                  }
+                ]
+              }
+            ],
+            "queries": [
+              {
+                "rel": "search",
+                "href": "/users",
+                "prompt": "Search",
+                "data": [
+                  {
+                    "name": "q",
+                    "value": ""
+                  },
+                  {
+                    "name": "page",
+                    "value": ""
+                  }
+                ]
+              }
+            ],
+            "template": {
+              "data": [
+                {
+                  "name": "name",
+                  "value": "",
+                  "prompt": "Name"
+                },
+                {
+                  "name": "age",
+                  "value": "18"
+                }
+              ]
+            }
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn empty_collection() {
+        let_assert!(Ok(collection) = Hal::new(()).to_collection_json("items"));
+
+        assert_json_snapshot!(collection, @r###"
+        {
+          "collection": {
+            "version": "1.0",
+            "items": []
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn nested_item_data() {
+        let hal = Hal::new(()).with_embedded(
+            "items",
+            Hal::new(json!({
+                "name": "Graham",
+                "address": {"street": "Main Street", "geo": {"lat": 1.5}},
+                "tags": ["a", {"b": true}],
+                "empty": [],
+                "none": {}
+            })),
+        );
+
+        let_assert!(Ok(collection) = hal.to_collection_json("items"));
+
+        assert_json_snapshot!(collection["collection"]["items"][0]["data"], @r###"
+        [
+          {
+            "name": "name",
+            "value": "Graham"
+          },
+          {
+            "name": "address.street",
+            "value": "Main Street"
+          },
+          {
+            "name": "address.geo.lat",
+            "value": 1.5
+          },
+          {
+            "name": "tags.0",
+            "value": "a"
+          },
+          {
+            "name": "tags.1.b",
+            "value": true
+          },
+          {
+            "name": "empty",
+            "value": null
+          },
+          {
+            "name": "none",
+            "value": null
+          }
+        ]
+        "###);
+    }
+
+    #[test]
+    fn item_not_an_object() {
+        let hal = Hal::new(()).with_embedded("items", Hal::new(42));
+
+        let_assert!(Err(Error::NotAnObject) = hal.to_collection_json("items"));
+    }
+}
@@ -0,0 +1,454 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::{values::LinkRelation, Error, Hal, UriTemplate};
+
+/// The length of the signature appended to signed cursors, in bytes.
+const SIGNATURE_LENGTH: usize = 32;
+
+/// Encodes keys into the opaque cursors used for cursor pagination, and decodes them again.
+///
+/// A key is anything that can be serialized, such as the timestamp and ID of the last item on a
+/// page. It is serialized as JSON and then base64 encoded, so cursors are opaque to clients but
+/// aren't secret. If a signing key is given then cursors are also signed with HMAC-SHA256, so that
+/// clients can't forge them or tamper with them.
+#[derive(Clone, Default)]
+pub struct CursorCodec {
+    signing_key: Option<Vec<u8>>,
+}
+
+/// A single page of a larger collection of items, navigated using cursors rather than page
+/// numbers.
+///
+/// The href is a URI Template with a `cursor` variable, such as `/events{?cursor}`, which is
+/// expanded with each of the cursors to build the self, `next` and `prev` links.
+#[derive(Debug)]
+pub struct CursorPage<T> {
+    pub href:      String,
+    pub items:     Vec<T>,
+    /// The link relation that the items are embedded under.
+    pub items_rel: String,
+    /// The cursor used to fetch this page, or `None` for the start of the collection.
+    pub cursor:    Option<String>,
+    /// The cursor for the next page, if there is one.
+    pub next:      Option<String>,
+    /// The cursor for the previous page, if there is one.
+    pub prev:      Option<String>,
+}
+
+/// The cursor given in the query string of a request for a page of a collection.
+///
+/// With the `axum` feature this can be extracted directly from a request.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+pub struct CursorQuery {
+    pub cursor: Option<String>,
+}
+
+/// The variables used to expand the href of a page.
+#[derive(Serialize)]
+struct CursorVariables<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<&'a str>,
+}
+
+impl CursorCodec {
+    /// Sign cursors with the given key, and reject any cursors that weren't signed with it.
+    #[must_use]
+    pub fn with_signing_key<K>(mut self, key: K) -> Self
+    where
+        K: AsRef<[u8]>,
+    {
+        self.signing_key = Some(key.as_ref().to_vec());
+
+        self
+    }
+
+    /// Encode a key into a cursor.
+    ///
+    /// # Errors
+    /// If the key can't be serialized into JSON.
+    pub fn encode<K>(&self, key: &K) -> Result<String, Error>
+    where
+        K: Serialize + ?Sized,
+    {
+        let mut cursor = serde_json::to_vec(key).map_err(Error::Serialization)?;
+
+        if let Some(mut mac) = self.mac()? {
+            mac.update(&cursor);
+            cursor.extend(mac.finalize().into_bytes());
+        }
+
+        Ok(URL_SAFE_NO_PAD.encode(cursor))
+    }
+
+    /// Decode a cursor back into the key that it was encoded from.
+    ///
+    /// # Errors
+    /// If the cursor wasn't produced by [`CursorCodec::encode`] with the same signing key, or
+    /// doesn't decode into the expected type of key.
+    pub fn decode<K>(&self, cursor: &str) -> Result<K, Error>
+    where
+        K: DeserializeOwned,
+    {
+        let cursor = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| Error::InvalidCursor)?;
+
+        let key = match self.mac()? {
+            None => cursor.as_slice(),
+            Some(mut mac) => {
+                let length = cursor
+                    .len()
+                    .checked_sub(SIGNATURE_LENGTH)
+                    .ok_or(Error::InvalidCursor)?;
+                let (key, signature) = cursor.split_at(length);
+
+                mac.update(key);
+                mac.verify_slice(signature)
+                    .map_err(|_| Error::InvalidCursor)?;
+
+                key
+            },
+        };
+
+        serde_json::from_slice(key).map_err(|_| Error::InvalidCursor)
+    }
+
+    /// Build the MAC used to sign cursors, if there is a signing key.
+    fn mac(&self) -> Result<Option<Hmac<Sha256>>, Error> {
+        self.signing_key
+            .as_ref()
+            .map(|key| Hmac::new_from_slice(key))
+            .transpose()
+            // HMAC accepts keys of any length, so this never actually fails.
+            .map_err(|_| Error::InvalidCursor)
+    }
+}
+
+impl std::fmt::Debug for CursorCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never include the signing key itself.
+        f.debug_struct("CursorCodec")
+            .field("signed", &self.signing_key.is_some())
+            .finish()
+    }
+}
+
+impl<T> CursorPage<T> {
+    /// Create a new page of items, embedded under the `item` link relation.
+    #[must_use]
+    pub fn new<H, I>(href: H, items: I) -> Self
+    where
+        H: ToString,
+        I: IntoIterator<Item = T>,
+    {
+        Self {
+            href:      href.to_string(),
+            items:     items.into_iter().collect(),
+            items_rel: LinkRelation::Item.to_string(),
+            cursor:    None,
+            next:      None,
+            prev:      None,
+        }
+    }
+
+    /// Set the link relation that the items are embedded under.
+    #[must_use]
+    pub fn with_items_rel<S>(mut self, value: S) -> Self
+    where
+        S: ToString,
+    {
+        self.items_rel = value.to_string();
+
+        self
+    }
+
+    /// Set the cursor that was used to fetch this page.
+    #[must_use]
+    pub fn with_cursor<S>(mut self, value: S) -> Self
+    where
+        S: ToString,
+    {
+        self.cursor = Some(value.to_string());
+
+        self
+    }
+
+    /// Set the cursor that was used to fetch this page, if there was one.
+    #[must_use]
+    pub fn maybe_with_cursor<S>(mut self, value: Option<S>) -> Self
+    where
+        S: ToString,
+    {
+        self.cursor = value.map(|value| value.to_string());
+
+        self
+    }
+
+    /// Set the cursor for the next page.
+    #[must_use]
+    pub fn with_next<S>(mut self, value: S) -> Self
+    where
+        S: ToString,
+    {
+        self.next = Some(value.to_string());
+
+        self
+    }
+
+    /// Set the cursor for the next page, if there is one.
+    #[must_use]
+    pub fn maybe_with_next<S>(mut self, value: Option<S>) -> Self
+    where
+        S: ToString,
+    {
+        self.next = value.map(|value| value.to_string());
+
+        self
+    }
+
+    /// Set the cursor for the previous page.
+    #[must_use]
+    pub fn with_prev<S>(mut self, value: S) -> Self
+    where
+        S: ToString,
+    {
+        self.prev = Some(value.to_string());
+
+        self
+    }
+
+    /// Set the cursor for the previous page, if there is one.
+    #[must_use]
+    pub fn maybe_with_prev<S>(mut self, value: Option<S>) -> Self
+    where
+        S: ToString,
+    {
+        self.prev = value.map(|value| value.to_string());
+
+        self
+    }
+
+    /// Convert the page into a HAL document.
+    ///
+    /// The items are embedded under the configured link relation as an array, even when there is
+    /// only one item or none at all. The self link is always present, and `next` and `prev` are
+    /// only present when there is a cursor for them.
+    ///
+    /// # Errors
+    /// If the href is not a valid URI Template.
+    pub fn into_hal<P>(self) -> Result<Hal<()>, Error>
+    where
+        T: Into<Hal<P>>,
+        P: Serialize + Send + Sync + 'static,
+    {
+        let template = UriTemplate::parse(&self.href)?;
+        let href = |cursor: Option<&str>| template.expand(&CursorVariables { cursor });
+
+        let mut hal = Hal::new(()).with_link(LinkRelation::SelfLink, href(self.cursor.as_deref())?);
+        if let Some(prev) = &self.prev {
+            hal = hal.with_link(LinkRelation::Prev, href(Some(prev))?);
+        }
+        if let Some(next) = &self.next {
+            hal = hal.with_link(LinkRelation::Next, href(Some(next))?);
+        }
+
+        Ok(hal.with_embedded_array(self.items_rel, self.items))
+    }
+}
+
+impl CursorQuery {
+    /// Get the cursor from the query string of a request, without the leading `?`.
+    #[must_use]
+    pub fn from_query(query: &str) -> Self {
+        let cursor = form_urlencoded::parse(query.as_bytes())
+            .find(|(name, _)| name == "cursor")
+            .map(|(_, value)| value.into_owned())
+            .filter(|value| !value.is_empty());
+
+        Self { cursor }
+    }
+
+    /// Decode the cursor into the key that it was encoded from, or `None` if there was no cursor.
+    ///
+    /// # Errors
+    /// If the cursor can't be decoded.
+    pub fn decode<K>(&self, codec: &CursorCodec) -> Result<Option<K>, Error>
+    where
+        K: DeserializeOwned,
+    {
+        self.cursor
+            .as_deref()
+            .map(|cursor| codec.decode(cursor))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+    use insta::assert_json_snapshot;
+    use serde_json::json;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Key {
+        timestamp: u64,
+        id:        u32,
+    }
+
+    #[test]
+    fn round_trip() {
+        let codec = CursorCodec::default();
+        let key = Key {
+            timestamp: 1_700_000_000,
+            id:        42,
+        };
+
+        let_assert!(Ok(cursor) = codec.encode(&key));
+        check!(cursor == "eyJ0aW1lc3RhbXAiOjE3MDAwMDAwMDAsImlkIjo0Mn0");
+        check!(codec.decode::<Key>(&cursor).ok() == Some(key));
+    }
+
+    #[test]
+    fn signed_round_trip() {
+        let codec = CursorCodec::default().with_signing_key("secret");
+
+        let_assert!(Ok(cursor) = codec.encode(&42));
+        check!(codec.decode::<u32>(&cursor).ok() == Some(42));
+
+        // Unsigned cursors, and cursors signed with a different key, are rejected.
+        let_assert!(Ok(unsigned) = CursorCodec::default().encode(&42));
+        let_assert!(Err(Error::InvalidCursor) = codec.decode::<u32>(&unsigned));
+        let other = CursorCodec::default().with_signing_key("other");
+        let_assert!(Err(Error::InvalidCursor) = other.decode::<u32>(&cursor));
+    }
+
+    #[test]
+    fn tampered_cursor() {
+        let codec = CursorCodec::default().with_signing_key("secret");
+        let_assert!(Ok(cursor) = codec.encode(&42));
+
+        let mut bytes = URL_SAFE_NO_PAD.decode(&cursor).unwrap();
+        bytes[0] = b'9';
+        let tampered = URL_SAFE_NO_PAD.encode(bytes);
+
+        let_assert!(Err(Error::InvalidCursor) = codec.decode::<u32>(&tampered));
+    }
+
+    #[test]
+    fn invalid_cursors() {
+        let codec = CursorCodec::default();
+
+        let_assert!(Err(Error::InvalidCursor) = codec.decode::<u32>("not base64!"));
+        let_assert!(Err(Error::InvalidCursor) = codec.decode::<u32>("bm90IGpzb24"));
+        let_assert!(Err(Error::InvalidCursor) = codec.decode::<Key>("NDI"));
+    }
+
+    #[test]
+    fn debug_hides_signing_key() {
+        let codec = CursorCodec::default().with_signing_key("secret");
+
+        check!(format!("{codec:?}") == "CursorCodec { signed: true }");
+    }
+
+    #[test]
+    fn page() {
+        let page = CursorPage::new(
+            "/events{?cursor}",
+            vec![Hal::new(json!({"id": 1})), Hal::new(json!({"id": 2}))],
+        )
+        .with_items_rel("events")
+        .with_cursor("abc")
+        .with_next("def")
+        .with_prev("ghi");
+
+        let_assert!(Ok(hal) = page.into_hal());
+
+        assert_json_snapshot!(hal, @r###"
+        {
+          "_links": {
+            "next": {
+              "href": "/events?cursor=def"
+            },
+            "prev": {
+              "href": "/events?cursor=ghi"
+            },
+            "self": {
+              "href": "/events?cursor=abc"
+            }
+          },
+          "_embedded": {
+            "events": [
+              {
+                "id": 1
+              },
+              {
+                "id": 2
+              }
+            ]
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn first_page() {
+        let page =
+            CursorPage::new("/events{?cursor}", vec![Hal::new(json!({"id": 1}))]).with_next("def");
+
+        let_assert!(Ok(hal) = page.into_hal());
+
+        assert_json_snapshot!(hal, @r###"
+        {
+          "_links": {
+            "next": {
+              "href": "/events?cursor=def"
+            },
+            "self": {
+              "href": "/events"
+            }
+          },
+          "_embedded": {
+            "item": [
+              {
+                "id": 1
+              }
+            ]
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn empty_page() {
+        let page = CursorPage::<Hal>::new("/events{?cursor}", vec![]).with_items_rel("events");
+
+        let_assert!(Ok(hal) = page.into_hal());
+        let_assert!(Ok(value) = serde_json::to_value(&hal));
+        check!(value["_embedded"] == json!({"events": []}));
+    }
+
+    #[test]
+    fn query() {
+        let codec = CursorCodec::default();
+        let_assert!(Ok(cursor) = codec.encode(&42));
+
+        let query = CursorQuery::from_query(&format!("size=10&cursor={cursor}"));
+        check!(query.decode::<u32>(&codec).ok() == Some(Some(42)));
+
+        check!(CursorQuery::from_query("size=10") == CursorQuery::default());
+        check!(
+            CursorQuery::from_query("cursor=")
+                .decode::<u32>(&codec)
+                .ok()
+                == Some(None)
+        );
+        let_assert!(
+            Err(Error::InvalidCursor) = CursorQuery::from_query("cursor=x").decode::<u32>(&codec)
+        );
+    }
+}
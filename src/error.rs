@@ -0,0 +1,61 @@
+/// Errors that can occur when building HAL documents.
+#[derive(Debug, derive_more::Display, derive_more::Error)]
+pub enum Error {
+    /// The payload could not be serialized into JSON.
+    #[display("Failed to serialize payload: {_0}")]
+    Serialization(serde_json::Error),
+
+    /// The payload serialized into something other than a JSON object, so can't be flattened into
+    /// the HAL document.
+    #[display("Payload must serialize into a JSON object")]
+    NotAnObject,
+
+    /// The payload contains a key that is reserved for use by the HAL document itself.
+    #[display("Payload contains reserved key: {_0}")]
+    ReservedKey(#[error(not(source))] String),
+
+    /// A string was not a valid RFC 6570 URI Template.
+    #[display("Invalid URI Template {template}: {reason}")]
+    InvalidUriTemplate {
+        template: String,
+        reason:   &'static str,
+    },
+
+    /// A HAL-FORMS template property has a regex that isn't valid, so values can't be checked
+    /// against it.
+    #[display("Invalid regex {regex}: {reason}")]
+    InvalidRegex { regex: String, reason: String },
+
+    /// The value provided for a URI Template variable can't be used in the template.
+    #[display("Invalid value for URI Template variable: {_0}")]
+    InvalidUriTemplateVariable(#[error(not(source))] String),
+
+    /// A document has a payload field with the same name as one of its link relations or
+    /// embedded documents, and the representation needs them to be a single property.
+    #[display("Property is used by both the payload and the document: {_0}")]
+    ConflictingProperty(#[error(not(source))] String),
+
+    /// A link relation is in CURIE form, but there is no CURIE registered with its prefix.
+    #[display("No CURIE registered for link relation: {_0}")]
+    UnknownCurie(#[error(not(source))] String),
+
+    /// A CURIE has no name, or its href has no `{rel}` placeholder.
+    #[display("Invalid CURIE: {_0}")]
+    InvalidCurie(#[error(not(source))] String),
+
+    /// A payload key can't be used as the name of an XML element.
+    #[display("Payload key can't be used as an XML element name: {_0}")]
+    InvalidXmlName(#[error(not(source))] String),
+
+    /// A document couldn't be parsed as HAL+XML.
+    #[display("Invalid HAL+XML document: {_0}")]
+    InvalidXml(#[error(not(source))] String),
+
+    /// A pagination cursor couldn't be decoded, or its signature didn't match.
+    #[display("Invalid pagination cursor")]
+    InvalidCursor,
+
+    /// A `Link` header couldn't be produced or parsed.
+    #[display("Invalid Link header: {_0}")]
+    InvalidLinkHeader(#[error(not(source))] String),
+}
@@ -1,24 +1,28 @@
+pub(crate) mod curie;
 #[allow(clippy::module_inception)]
 mod hal;
 mod link;
 mod option;
+mod payload;
 mod property;
 mod template;
 
 pub use hal::*;
 pub use link::*;
 pub use option::*;
+pub use payload::*;
 pub use property::*;
 pub use template::*;
 
 #[cfg(test)]
 mod tests {
-    use assert2::let_assert;
+    use assert2::{check, let_assert};
     use insta::assert_json_snapshot;
+    use serde::{Deserialize, Serialize};
     use serde_json::json;
 
     use super::*;
-    use crate::values::HttpMethod;
+    use crate::{utils::single_multiple::SingleOrMultiple, values::HttpMethod, Error};
 
     #[test]
     fn serialize_empty() {
@@ -166,6 +170,42 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn serialize_embedded_into_hal() {
+        struct Order {
+            total: u32,
+        }
+
+        impl From<Order> for Hal {
+            fn from(order: Order) -> Self {
+                Hal::new(json!({"total": order.total}))
+            }
+        }
+
+        let sut = Hal::new(())
+            .with_embedded("orders", Order { total: 30 })
+            .maybe_with_embedded("orders", Some(Order { total: 20 }))
+            .maybe_with_embedded::<_, Order, _>("orders", None);
+
+        let result = serde_json::to_value(sut);
+        let_assert!(Ok(value) = result);
+
+        assert_json_snapshot!(value, @r###"
+        {
+          "_embedded": {
+            "orders": [
+              {
+                "total": 30
+              },
+              {
+                "total": 20
+              }
+            ]
+          }
+        }
+        "###);
+    }
+
     #[test]
     fn serialize_hal_example() {
         let sut = Hal::new(json!({
@@ -633,4 +673,548 @@ mod tests {
         }
         "###);
     }
+
+    #[test]
+    fn deserialize_hal_example() {
+        let input = json!({
+          "_links": {
+            "self": { "href": "/orders" },
+            "find": { "href": "/orders{?id}", "templated": true }
+          },
+          "_embedded": {
+            "orders": [
+              {
+                "_links": { "self": { "href": "/orders/123" } },
+                "total": 30.00
+              },
+              {
+                "_links": { "self": { "href": "/orders/124" } },
+                "total": 20.00
+              }
+            ],
+            "customer": {
+              "name": "Bob"
+            }
+          },
+          "currentlyProcessing": 14
+        });
+
+        let result: Result<Hal, _> = serde_json::from_value(input.clone());
+        let_assert!(Ok(sut) = result);
+
+        let_assert!(Some(SingleOrMultiple::Single(link)) = sut.links.get("find"));
+        check!(link.href == "/orders{?id}");
+        check!(link.templated);
+
+        let_assert!(Some(SingleOrMultiple::Multiple(orders)) = sut.embedded.get("orders"));
+        check!(orders.len() == 2);
+        check!(orders[1].payload().downcast_ref() == Some(&json!({"total": 20.0})));
+
+        let_assert!(Some(SingleOrMultiple::Single(customer)) = sut.embedded.get("customer"));
+        check!(customer.payload().downcast_ref() == Some(&json!({"name": "Bob"})));
+
+        check!(sut.payload() == &json!({"currentlyProcessing": 14}));
+
+        let result = serde_json::to_value(sut);
+        let_assert!(Ok(value) = result);
+        check!(value == input);
+    }
+
+    #[test]
+    fn deserialize_templates() {
+        let input = json!({
+          "_templates": {
+            "default": {
+              "contentType": "application/json",
+              "method": "POST",
+              "target": "/abc",
+              "properties": [
+                {
+                  "name": "name",
+                  "required": true,
+                  "readOnly": true,
+                  "maxLength": 500,
+                  "options": {
+                    "inline": ["a", {"prompt": "B", "value": "b"}],
+                    "selectedValues": ["b"]
+                  }
+                },
+                {
+                  "name": "other",
+                  "options": {
+                    "link": { "href": "/options{?q}", "templated": true },
+                    "maxItems": 1
+                  }
+                }
+              ]
+            }
+          }
+        });
+
+        let result: Result<Hal, _> = serde_json::from_value(input);
+        let_assert!(Ok(sut) = result);
+
+        let_assert!(Some(template) = sut.templates.get("default"));
+        check!(template.method.as_deref() == Some("POST"));
+        check!(template.properties.len() == 2);
+
+        let name = &template.properties[0];
+        check!(name.required);
+        check!(name.readonly);
+        check!(name.max_length == Some(500));
+        let_assert!(Some(TemplateOptions::Inline { inline, .. }) = &name.options);
+        check!(inline[0] == InlineOption::new("a"));
+        check!(inline[1] == InlineOption::new("b").with_prompt("B"));
+
+        let_assert!(
+            Some(TemplateOptions::Link {
+                link,
+                max_items,
+                ..
+            }) = &template.properties[1].options
+        );
+        check!(link == &Link::new("/options{?q}").templated());
+        check!(max_items == &Some(1));
+    }
+
+    #[test]
+    fn deserialize_unknown_fields() {
+        let input = json!({
+          "_links": {
+            "self": {
+              "href": "/",
+              "x-custom": "link",
+              "hints": {
+                "allow": ["GET"],
+                "x-custom": "hints"
+              }
+            }
+          },
+          "_templates": {
+            "default": {
+              "x-custom": "template",
+              "properties": [
+                {
+                  "name": "name",
+                  "x-custom": "property"
+                }
+              ]
+            }
+          }
+        });
+
+        let result: Result<Hal, _> = serde_json::from_value(input.clone());
+        let_assert!(Ok(sut) = result);
+
+        let_assert!(Some(SingleOrMultiple::Single(link)) = sut.links.get("self"));
+        check!(link.extensions.get("x-custom") == Some(&json!("link")));
+
+        let result = serde_json::to_value(sut);
+        let_assert!(Ok(value) = result);
+        check!(value == input);
+    }
+
+    #[test]
+    fn round_trip_link_hints() {
+        let sut = Hal::new(()).with_link(
+            "self",
+            Link::new("/").with_hints(
+                LinkHints::default()
+                    .with_allow(HttpMethod::GET)
+                    .with_format(
+                        "application/example+json",
+                        LinkHintFormat::default().with_deprecated(),
+                    )
+                    .with_accept_post("application/example+json", LinkHintFormat::default())
+                    .with_auth_scheme(LinkHintAuthSchemes::new("Basic").with_realm("Test"))
+                    .with_status_deprecated(),
+            ),
+        );
+
+        let result = serde_json::to_value(sut);
+        let_assert!(Ok(value) = result);
+
+        let result: Result<Hal, _> = serde_json::from_value(value.clone());
+        let_assert!(Ok(parsed) = result);
+
+        let result = serde_json::to_value(parsed);
+        let_assert!(Ok(round_tripped) = result);
+        check!(round_tripped == value);
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        total:    f64,
+        currency: String,
+    }
+
+    #[test]
+    fn serialize_typed_payload() {
+        let sut = Hal::new(Order {
+            total:    30.0,
+            currency: "USD".to_string(),
+        })
+        .with_link("self", "/orders/123")
+        .with_embedded(
+            "related",
+            Hal::new(Order {
+                total:    20.0,
+                currency: "GBP".to_string(),
+            }),
+        );
+
+        check!(sut.payload().currency == "USD");
+
+        let_assert!(Some(SingleOrMultiple::Single(related)) = sut.embedded.get("related"));
+        let_assert!(Some(order) = related.payload().downcast_ref::<Order>());
+        check!(order.currency == "GBP");
+
+        let result = serde_json::to_value(sut);
+        let_assert!(Ok(value) = result);
+
+        assert_json_snapshot!(value, @r###"
+        {
+          "_links": {
+            "self": {
+              "href": "/orders/123"
+            }
+          },
+          "_embedded": {
+            "related": {
+              "total": 20.0,
+              "currency": "GBP"
+            }
+          },
+          "total": 30.0,
+          "currency": "USD"
+        }
+        "###);
+    }
+
+    #[test]
+    fn deserialize_typed_payload() {
+        let input = json!({
+          "_links": {
+            "self": { "href": "/orders/123" }
+          },
+          "total": 30.0,
+          "currency": "USD"
+        });
+
+        let result: Result<Hal<Order>, _> = serde_json::from_value(input);
+        let_assert!(Ok(sut) = result);
+
+        check!(sut.links.contains_key("self"));
+        check!(
+            sut.into_payload()
+                == Order {
+                    total:    30.0,
+                    currency: "USD".to_string(),
+                }
+        );
+    }
+
+    #[test]
+    fn embed_embedded_document() {
+        let input = json!({
+          "_embedded": {
+            "inner": { "name": "test" }
+          }
+        });
+
+        let result: Result<Hal, _> = serde_json::from_value(input);
+        let_assert!(Ok(parsed) = result);
+        let_assert!(Some(SingleOrMultiple::Single(inner)) = parsed.embedded.into_values().next());
+
+        let sut = Hal::new(()).with_embedded("outer", inner);
+
+        let_assert!(Some(SingleOrMultiple::Single(outer)) = sut.embedded.get("outer"));
+        check!(outer.payload().downcast_ref() == Some(&json!({"name": "test"})));
+    }
+
+    #[test]
+    fn try_new_object() {
+        let result = Hal::try_new(json!({"name": "hal"}));
+        let_assert!(Ok(sut) = result);
+
+        check!(sut.payload() == &json!({"name": "hal"}));
+    }
+
+    #[test]
+    fn try_new_empty() {
+        let result = Hal::try_new(());
+        check!(result.is_ok());
+    }
+
+    #[test]
+    fn try_new_not_object() {
+        let result = Hal::try_new(42);
+        let_assert!(Err(Error::NotAnObject) = result);
+    }
+
+    #[test]
+    fn try_new_reserved_key() {
+        let result = Hal::try_new(json!({"name": "hal", "_embedded": {}}));
+        let_assert!(Err(Error::ReservedKey(key)) = result);
+
+        check!(key == "_embedded");
+    }
+
+    #[test]
+    fn try_new_unserializable() {
+        let result = Hal::try_new(std::collections::HashMap::from([((1, 2), "value")]));
+        let_assert!(Err(Error::Serialization(_)) = result);
+    }
+
+    #[test]
+    fn serialize_curies() {
+        let sut = Hal::new(())
+            .with_curie("acme", "https://example.com/rels/{rel}")
+            .with_link("acme:widgets", "/widgets");
+
+        let result = serde_json::to_value(sut);
+        let_assert!(Ok(value) = result);
+
+        assert_json_snapshot!(value, @r###"
+        {
+          "_links": {
+            "acme:widgets": {
+              "href": "/widgets"
+            },
+            "curies": [
+              {
+                "href": "https://example.com/rels/{rel}",
+                "templated": true,
+                "name": "acme"
+              }
+            ]
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn validate_curies() {
+        let sut = Hal::new(())
+            .with_curie("acme", "https://example.com/rels/{rel}")
+            .with_link("self", "/")
+            .with_link("acme:widgets", "/widgets")
+            .with_link("https://example.com/rels/gadgets", "/gadgets")
+            .with_embedded(
+                "acme:widget",
+                Hal::new(()).with_link("acme:parts", "/widgets/1/parts"),
+            );
+
+        check!(sut.validate_curies().is_ok());
+    }
+
+    #[test]
+    fn validate_unknown_curie() {
+        let sut = Hal::new(())
+            .with_curie("acme", "https://example.com/rels/{rel}")
+            .with_embedded(
+                "acme:widget",
+                Hal::new(()).with_link("other:parts", "/parts"),
+            );
+
+        let_assert!(Err(Error::UnknownCurie(rel)) = sut.validate_curies());
+        check!(rel == "other:parts");
+
+        let sut = Hal::new(()).with_link("acme:widgets", "/w");
+        let_assert!(Err(Error::UnknownCurie(rel)) = sut.validate_curies());
+        check!(rel == "acme:widgets");
+    }
+
+    #[test]
+    fn validate_absolute_uris() {
+        let sut = Hal::new(())
+            .with_curie("acme", "https://example.com/rels/{rel}")
+            .with_link("urn:isbn:0451450523", "/books/1")
+            .with_link("tag:example.com,2024:widgets", "/widgets")
+            .with_link("https://example.com/rels/parts", "/parts")
+            .with_embedded(
+                "acme:widget",
+                Hal::new(()).with_link("acme:parts", "/parts"),
+            );
+
+        check!(sut.validate_curies().is_ok());
+    }
+
+    #[test]
+    fn validate_invalid_curie() {
+        let nameless = Link::new("https://example.com/rels/{rel}").templated();
+        let sut = Hal::new(()).with_embedded("widget", Hal::new(()).with_link("curies", nameless));
+        let_assert!(Err(Error::InvalidCurie(href)) = sut.validate_curies());
+        check!(href == "https://example.com/rels/{rel}");
+
+        let sut = Hal::new(()).with_curie("acme", "https://example.com/rels/{name}");
+        let_assert!(Err(Error::InvalidCurie(href)) = sut.validate_curies());
+        check!(href == "https://example.com/rels/{name}");
+    }
+
+    #[test]
+    fn serialize_compacted_curies() {
+        let sut = Hal::new(())
+            .with_curie("acme", "https://example.com/rels/{rel}")
+            .with_link("https://example.com/rels/widgets", "/widgets")
+            .with_link("acme:widgets", "/more-widgets")
+            .with_link("https://example.org/other", "/other")
+            .with_embedded(
+                "https://example.com/rels/widget",
+                Hal::new(())
+                    .with_curie("parts", "https://example.com/parts/{rel}.html")
+                    .with_link("https://example.com/rels/owner", "/owner")
+                    .with_link("https://example.com/parts/wheel.html", "/wheel"),
+            )
+            .compact_curies();
+
+        let result = serde_json::to_value(sut);
+        let_assert!(Ok(value) = result);
+
+        assert_json_snapshot!(value, @r###"
+        {
+          "_links": {
+            "acme:widgets": [
+              {
+                "href": "/more-widgets"
+              },
+              {
+                "href": "/widgets"
+              }
+            ],
+            "curies": [
+              {
+                "href": "https://example.com/rels/{rel}",
+                "templated": true,
+                "name": "acme"
+              }
+            ],
+            "https://example.org/other": {
+              "href": "/other"
+            }
+          },
+          "_embedded": {
+            "acme:widget": {
+              "_links": {
+                "acme:owner": {
+                  "href": "/owner"
+                },
+                "curies": [
+                  {
+                    "href": "https://example.com/parts/{rel}.html",
+                    "templated": true,
+                    "name": "parts"
+                  }
+                ],
+                "parts:wheel": {
+                  "href": "/wheel"
+                }
+              }
+            }
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn expand_curies() {
+        let input = json!({
+          "_links": {
+            "curies": [{ "href": "https://example.com/rels/{rel}", "templated": true, "name": "acme" }],
+            "acme:widgets": { "href": "/widgets" },
+            "self": { "href": "/" }
+          },
+          "_embedded": {
+            "acme:widget": {
+              "_links": {
+                "acme:owner": { "href": "/owner" }
+              }
+            }
+          }
+        });
+
+        let result: Result<Hal, _> = serde_json::from_value(input);
+        let_assert!(Ok(sut) = result);
+
+        check!(sut.links.contains_key("self"));
+        check!(sut.links.contains_key("https://example.com/rels/widgets"));
+        check!(!sut.links.contains_key("acme:widgets"));
+
+        let_assert!(
+            Some(SingleOrMultiple::Single(widget)) =
+                sut.embedded.get("https://example.com/rels/widget")
+        );
+        check!(widget.links.contains_key("https://example.com/rels/owner"));
+    }
+
+    #[test]
+    fn expand_curies_leaves_absolute_uris() {
+        let sut = Hal::new(())
+            .with_curie("acme", "https://example.com/rels/{rel}")
+            .with_link("acme:widgets", "/widgets")
+            .with_link("urn:isbn:0451450523", "/books/1")
+            .expand_curies();
+
+        let rels = sut.links.keys().map(String::as_str).collect::<Vec<_>>();
+        check!(
+            rels == [
+                "curies",
+                "https://example.com/rels/widgets",
+                "urn:isbn:0451450523"
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_and_compact_round_trip() {
+        let input = json!({
+          "_links": {
+            "curies": [{ "href": "https://example.com/rels/{rel}", "templated": true, "name": "acme" }],
+            "acme:widgets": { "href": "/widgets" }
+          }
+        });
+
+        let result: Result<Hal, _> = serde_json::from_value(input.clone());
+        let_assert!(Ok(sut) = result);
+        check!(sut.links.contains_key("https://example.com/rels/widgets"));
+
+        let result = serde_json::to_value(sut.compact_curies());
+        let_assert!(Ok(value) = result);
+        check!(value == input);
+    }
+
+    #[test]
+    fn curies_round_trip() {
+        let input = json!({
+          "_links": {
+            "acme:widgets": { "href": "/widgets" },
+            "curies": [{ "href": "https://example.com/rels/{rel}", "templated": true, "name": "acme" }],
+            "self": { "href": "/" }
+          },
+          "_embedded": {
+            "acme:widget": {
+              "_links": {
+                "acme:owner": { "href": "/users/1" },
+                "other:parts": { "href": "/widgets/1/parts" },
+                "curies": [{ "href": "https://example.org/{rel}", "templated": true, "name": "other" }]
+              },
+              "name": "Widget"
+            },
+            "item": {
+              "_links": {
+                "urn:isbn:0451450523": { "href": "/books/1" }
+              }
+            }
+          },
+          "name": "Root"
+        });
+
+        let result: Result<Hal, _> = serde_json::from_value(input.clone());
+        let_assert!(Ok(sut) = result);
+        check!(sut.links.contains_key("https://example.com/rels/widgets"));
+
+        let result = serde_json::to_value(&sut);
+        let_assert!(Ok(value) = result);
+        check!(value == input);
+    }
 }
@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{utils::single_multiple::SingleOrMultiple, Error, Link};
+
+/// The link relation used to define the CURIEs in a HAL document.
+pub(crate) const CURIES_REL: &str = "curies";
+
+/// The schemes of absolute URIs that have no `//` after the `:`, and so would otherwise look like
+/// link relations in CURIE form.
+const OPAQUE_SCHEMES: [&str; 4] = ["urn", "tag", "mailto", "data"];
+
+/// A single CURIE definition, split into the parts of the href either side of the `{rel}`
+/// placeholder.
+#[derive(Debug, Clone)]
+struct Curie {
+    name:   String,
+    prefix: String,
+    suffix: String,
+}
+
+/// The set of CURIEs that are available to a HAL document, including any inherited from the
+/// documents that it is embedded in.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Curies(Vec<Curie>);
+
+impl Curies {
+    /// Build a new set of CURIEs from these, plus any defined in the given links.
+    /// CURIEs from the links take priority over existing ones with the same name, and any
+    /// definitions that aren't valid are ignored.
+    pub(crate) fn extend_from(&self, links: &BTreeMap<String, SingleOrMultiple<Link>>) -> Self {
+        let mut curies = self.0.clone();
+        curies.extend(definitions(links).filter_map(|link| Curie::parse(link).ok()));
+
+        Self(curies)
+    }
+
+    /// Compact the given link relation into CURIE form, if there is a CURIE that matches it.
+    pub(crate) fn compact(&self, rel: &str) -> Option<String> {
+        self.0.iter().rev().find_map(|curie| {
+            let reference = rel
+                .strip_prefix(&curie.prefix)?
+                .strip_suffix(&curie.suffix)
+                .filter(|reference| !reference.is_empty())?;
+
+            Some(format!("{}:{reference}", curie.name))
+        })
+    }
+
+    /// Check if the given link relation is acceptable with these CURIEs.
+    /// This is true unless the relation is in CURIE form with an unknown prefix.
+    pub(crate) fn is_known(&self, rel: &str) -> bool {
+        rel.split_once(':').map_or(true, |(name, reference)| {
+            self.find(name).is_some()
+                || name.is_empty()
+                || reference.starts_with("//")
+                || OPAQUE_SCHEMES.contains(&name)
+        })
+    }
+
+    /// Expand the given link relation from CURIE form, if it is one.
+    ///
+    /// A link relation is only in CURIE form if the part before the `:` is the name of one of
+    /// these CURIEs. Anything else, such as `urn:isbn:0451450523`, is an absolute URI.
+    pub(crate) fn expand(&self, rel: &str) -> Option<String> {
+        let (name, reference) = rel.split_once(':')?;
+        let curie = self.find(name)?;
+
+        Some(format!("{}{reference}{}", curie.prefix, curie.suffix))
+    }
+
+    /// The names and IRI prefixes of the CURIEs that have nothing after the `{rel}` placeholder,
+    /// and so can be used as prefixes of compact IRIs.
+    pub(crate) fn prefixes(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .filter(|curie| curie.suffix.is_empty())
+            .map(|curie| (curie.name.as_str(), curie.prefix.as_str()))
+    }
+
+    /// Find the CURIE with the given name.
+    fn find(&self, name: &str) -> Option<&Curie> {
+        self.0.iter().rev().find(|curie| curie.name == name)
+    }
+}
+
+impl Curie {
+    /// Parse a single CURIE definition from its link.
+    ///
+    /// # Errors
+    /// If the link has no name, or its href has no `{rel}` placeholder.
+    fn parse(link: &Link) -> Result<Self, Error> {
+        let invalid = || Error::InvalidCurie(link.href.clone());
+
+        let name = link.name.clone().ok_or_else(invalid)?;
+        let (prefix, suffix) = link.href.split_once("{rel}").ok_or_else(invalid)?;
+
+        Ok(Self {
+            name,
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+        })
+    }
+}
+
+/// Check that every CURIE defined in the given links has a name and an href with a `{rel}`
+/// placeholder.
+pub(crate) fn validate(links: &BTreeMap<String, SingleOrMultiple<Link>>) -> Result<(), Error> {
+    definitions(links).try_for_each(|link| Curie::parse(link).map(|_| ()))
+}
+
+/// Iterate over the links that define CURIEs.
+fn definitions(links: &BTreeMap<String, SingleOrMultiple<Link>>) -> impl Iterator<Item = &Link> {
+    links
+        .get(CURIES_REL)
+        .into_iter()
+        .flat_map(SingleOrMultiple::iter)
+}
+
+/// Rename the link relations of the given values using the provided function, merging together any
+/// values that end up with the same relation.
+pub(crate) fn rename_rels<I, V, F>(values: I, rename: F) -> BTreeMap<String, SingleOrMultiple<V>>
+where
+    I: IntoIterator<Item = (String, SingleOrMultiple<V>)>,
+    V: Serialize,
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = BTreeMap::new();
+
+    for (rel, value) in values {
+        let rel = rename(&rel).unwrap_or(rel);
+
+        let value = match result.remove(&rel) {
+            None => value,
+            Some(existing) => SingleOrMultiple::merge(existing, value),
+        };
+
+        result.insert(rel, value);
+    }
+
+    result
+}
@@ -1,47 +1,115 @@
 use std::collections::BTreeMap;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize, Serializer};
 use serde_json::Value;
 
-use crate::{utils::single_multiple::SingleOrMultiple, Link, Template};
+use super::curie::{self, rename_rels, Curies, CURIES_REL};
+use crate::{utils::single_multiple::SingleOrMultiple, AnyPayload, Error, Link, Template};
+
+/// The keys in a HAL document that are used by the document itself, and so can't appear in the
+/// payload.
+const RESERVED_KEYS: [&str; 3] = ["_links", "_embedded", "_templates"];
 
 /// Representation of a HAL document.
-#[derive(Debug, Serialize)]
-pub struct Hal {
-    #[serde(rename = "_links")]
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+///
+/// The payload is kept as the type that it was provided as, and is only flattened into the
+/// document when it is serialized. Any link relations in CURIE form are expanded into full URIs
+/// when the document is deserialized, and documents that register CURIEs are compacted again when
+/// they are serialized, so that they round trip unchanged.
+#[derive(Debug, Deserialize)]
+#[serde(from = "ParsedHal<P>", bound(deserialize = "P: Deserialize<'de>"))]
+pub struct Hal<P = Value> {
     pub links: BTreeMap<String, SingleOrMultiple<Link>>,
 
-    #[serde(rename = "_embedded")]
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
-    pub embedded: BTreeMap<String, SingleOrMultiple<Hal>>,
+    pub embedded: BTreeMap<String, SingleOrMultiple<Hal<AnyPayload>>>,
 
-    #[serde(rename = "_templates")]
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub templates: BTreeMap<String, Template>,
 
+    pub payload: P,
+
+    /// Whether link relations should be compacted into CURIE form when serializing.
+    /// This applies to the entire document, including any embedded documents.
+    pub compact_curies: bool,
+}
+
+/// Representation of a HAL document as it is deserialized, before any CURIEs are expanded.
+#[derive(Deserialize)]
+struct ParsedHal<P> {
+    #[serde(rename = "_links", default)]
+    links: BTreeMap<String, SingleOrMultiple<Link>>,
+
+    #[serde(rename = "_embedded", default)]
+    embedded: BTreeMap<String, SingleOrMultiple<Hal<AnyPayload>>>,
+
+    #[serde(rename = "_templates", default)]
+    templates: BTreeMap<String, Template>,
+
     #[serde(flatten)]
-    pub payload: Value,
+    payload: P,
 }
 
-impl Hal {
+impl<P> From<ParsedHal<P>> for Hal<P> {
+    fn from(parsed: ParsedHal<P>) -> Self {
+        Self {
+            links:          parsed.links,
+            embedded:       parsed.embedded,
+            templates:      parsed.templates,
+            payload:        parsed.payload,
+            compact_curies: false,
+        }
+        .expand_parsed_curies()
+    }
+}
+
+impl<P> Hal<P> {
     /// Create a new HAL document for the given payload value.
-    ///
-    /// # Panics
-    /// This will panic if the value provided can not be serialized into JSON for some reason.
     #[must_use]
-    pub fn new<V>(value: V) -> Self
-    where
-        V: Serialize,
-    {
-        let payload = serde_json::to_value(value).unwrap();
-
+    pub fn new(payload: P) -> Self {
         Self {
             payload,
             links: BTreeMap::new(),
             embedded: BTreeMap::new(),
             templates: BTreeMap::new(),
+            compact_curies: false,
+        }
+    }
+
+    /// Create a new HAL document for the given payload value, checking that the payload can be
+    /// represented correctly.
+    ///
+    /// The payload is serialized once up front to check that it is a JSON object (or nothing at
+    /// all) that doesn't contain any of the keys reserved for the HAL document.
+    ///
+    /// # Errors
+    /// If the payload can't be serialized, doesn't serialize into a JSON object, or contains any
+    /// of the `_links`, `_embedded` or `_templates` keys.
+    pub fn try_new(payload: P) -> Result<Self, Error>
+    where
+        P: Serialize,
+    {
+        match serde_json::to_value(&payload).map_err(Error::Serialization)? {
+            Value::Null => {},
+            Value::Object(values) => {
+                if let Some(key) = RESERVED_KEYS.iter().find(|key| values.contains_key(**key)) {
+                    return Err(Error::ReservedKey((*key).to_string()));
+                }
+            },
+            _ => return Err(Error::NotAnObject),
         }
+
+        Ok(Self::new(payload))
+    }
+
+    /// Get the payload of the HAL document.
+    #[must_use]
+    pub fn payload(&self) -> &P {
+        &self.payload
+    }
+
+    /// Consume the HAL document, returning the payload.
+    #[must_use]
+    pub fn into_payload(self) -> P {
+        self.payload
     }
 
     /// Add a new link to a HAL document.
@@ -78,15 +146,92 @@ impl Hal {
         }
     }
 
+    /// Add several new links to a HAL document, such as those parsed from a `Link` header by
+    /// [`parse_link_header`](crate::parse_link_header).
+    #[must_use]
+    pub fn with_links<I, N, L>(self, links: I) -> Self
+    where
+        I: IntoIterator<Item = (N, L)>,
+        N: ToString,
+        L: Into<Link>,
+    {
+        links
+            .into_iter()
+            .fold(self, |hal, (name, link)| hal.with_link(name, link))
+    }
+
+    /// Register a CURIE on the HAL document.
+    ///
+    /// The href is a URI Template containing a `{rel}` placeholder, which is expanded with the part
+    /// of the link relation after the CURIE name. For example, with a CURIE named `acme` and an
+    /// href of `https://example.com/rels/{rel}`, the link relation `acme:widgets` is equivalent to
+    /// `https://example.com/rels/widgets`.
+    #[must_use]
+    pub fn with_curie<N, H>(mut self, name: N, href: H) -> Self
+    where
+        N: ToString,
+        H: ToString,
+    {
+        let link = Link::new(href).templated().with_name(name);
+
+        // CURIEs are always rendered as an array, even when there's only one of them.
+        let curies = match self.links.remove(CURIES_REL) {
+            None => SingleOrMultiple::Multiple(vec![link]),
+            Some(curies) => curies.insert(link),
+        };
+
+        self.links.insert(CURIES_REL.to_string(), curies);
+
+        self
+    }
+
+    /// Indicate that any link relations that are full URIs should be compacted into CURIE form,
+    /// using the registered CURIEs, when the document is serialized.
+    #[must_use]
+    pub fn compact_curies(mut self) -> Self {
+        self.compact_curies = true;
+
+        self
+    }
+
+    /// Check that every link relation in CURIE form - in this document or any embedded documents -
+    /// has a registered CURIE, and that every registered CURIE has a name and an href with a
+    /// `{rel}` placeholder. Embedded documents can use any CURIEs registered on their parents.
+    ///
+    /// Relations that are absolute URIs, such as `https://example.com/rels/widgets` or
+    /// `urn:isbn:0451450523`, are not in CURIE form unless there is a CURIE registered with the
+    /// same name as their scheme.
+    ///
+    /// # Errors
+    /// If any link relation uses a CURIE name that isn't registered, or any CURIE has no name or
+    /// its href has no `{rel}` placeholder.
+    pub fn validate_curies(&self) -> Result<(), Error> {
+        self.validate_curies_with(&Curies::default())
+    }
+
+    /// Expand any link relations in CURIE form into full URIs, using the registered CURIEs.
+    /// This applies to the entire document, including any embedded documents, which can use any
+    /// CURIEs registered on their parents.
+    ///
+    /// This is done automatically when a document is deserialized, and the `curies` links are
+    /// kept so that the relations are compacted again when it is serialized.
+    #[must_use]
+    pub fn expand_curies(mut self) -> Self {
+        self.expand_curies_with(&Curies::default());
+
+        self
+    }
+
     /// Add a new embedded HAL document to the HAL document.
     #[must_use]
-    pub fn with_embedded<N, H>(mut self, name: N, value: H) -> Self
+    pub fn with_embedded<N, H, E>(mut self, name: N, value: H) -> Self
     where
         N: ToString,
-        H: Into<Hal>,
+        H: Into<Hal<E>>,
+        E: Serialize + Send + Sync + 'static,
     {
         let name = name.to_string();
-        let value = value.into();
+        let value = value.into().into_any();
 
         let embedded = match self.embedded.remove(&name) {
             None => SingleOrMultiple::Single(value),
@@ -100,10 +245,11 @@ impl Hal {
 
     /// Add a new embedded HAL document to a HAL document.
     #[must_use]
-    pub fn maybe_with_embedded<N, L>(self, name: N, embedded: Option<L>) -> Self
+    pub fn maybe_with_embedded<N, H, E>(self, name: N, embedded: Option<H>) -> Self
     where
         N: ToString,
-        L: Into<Hal>,
+        H: Into<Hal<E>>,
+        E: Serialize + Send + Sync + 'static,
     {
         if let Some(embedded) = embedded {
             self.with_embedded(name, embedded)
@@ -112,6 +258,26 @@ impl Hal {
         }
     }
 
+    /// Embed the given HAL documents under the link relation as an array, even when there is only
+    /// one of them or none at all, so that clients can always treat them as a list.
+    #[must_use]
+    pub(crate) fn with_embedded_array<N, I, H, E>(mut self, name: N, values: I) -> Self
+    where
+        N: ToString,
+        I: IntoIterator<Item = H>,
+        H: Into<Hal<E>>,
+        E: Serialize + Send + Sync + 'static,
+    {
+        let values = values
+            .into_iter()
+            .map(|value| value.into().into_any())
+            .collect();
+        self.embedded
+            .insert(name.to_string(), SingleOrMultiple::Multiple(values));
+
+        self
+    }
+
     /// Add a new action template to the HAL-FORMS document.
     #[must_use]
     pub fn with_template<N, T>(mut self, name: N, value: T) -> Self
@@ -137,4 +303,208 @@ impl Hal {
             self
         }
     }
+
+    /// Remove all templates from this document and any documents embedded into it, leaving a
+    /// plain HAL document.
+    #[must_use]
+    pub fn without_templates(mut self) -> Self {
+        self.strip_templates();
+
+        self
+    }
+
+    fn strip_templates(&mut self) {
+        self.templates.clear();
+
+        for embedded in self
+            .embedded
+            .values_mut()
+            .flat_map(SingleOrMultiple::iter_mut)
+        {
+            embedded.strip_templates();
+        }
+    }
+
+    /// Convert this into a HAL document with a type-erased payload, suitable for embedding.
+    fn into_any(self) -> Hal<AnyPayload>
+    where
+        P: Serialize + Send + Sync + 'static,
+    {
+        Hal {
+            links:          self.links,
+            embedded:       self.embedded,
+            templates:      self.templates,
+            payload:        AnyPayload::new(self.payload),
+            compact_curies: self.compact_curies,
+        }
+    }
+
+    /// Expand any link relations in CURIE form in a document that has just been parsed, and mark
+    /// every document that registers CURIEs to be compacted again when it is serialized, so that
+    /// it round trips unchanged.
+    pub(crate) fn expand_parsed_curies(mut self) -> Self {
+        self.compact_registered_curies();
+
+        self.expand_curies()
+    }
+
+    /// Mark this document, and any embedded documents, to be compacted when serialized if they
+    /// register any CURIEs.
+    fn compact_registered_curies(&mut self) {
+        self.compact_curies = self.links.contains_key(CURIES_REL);
+
+        for embedded in self
+            .embedded
+            .values_mut()
+            .flat_map(SingleOrMultiple::iter_mut)
+        {
+            embedded.compact_registered_curies();
+        }
+    }
+
+    /// Check that every link relation in CURIE form has a registered CURIE, including those
+    /// inherited from parent documents.
+    fn validate_curies_with(&self, inherited: &Curies) -> Result<(), Error> {
+        curie::validate(&self.links)?;
+        let curies = inherited.extend_from(&self.links);
+
+        if let Some(rel) = self
+            .links
+            .keys()
+            .chain(self.embedded.keys())
+            .find(|rel| !curies.is_known(rel))
+        {
+            return Err(Error::UnknownCurie(rel.clone()));
+        }
+
+        self.embedded
+            .values()
+            .flat_map(SingleOrMultiple::iter)
+            .try_for_each(|embedded| embedded.validate_curies_with(&curies))
+    }
+
+    /// Expand any link relations in CURIE form, using the registered CURIEs including those
+    /// inherited from parent documents.
+    fn expand_curies_with(&mut self, inherited: &Curies) {
+        let curies = inherited.extend_from(&self.links);
+
+        self.links = rename_rels(std::mem::take(&mut self.links), |rel| curies.expand(rel));
+        self.embedded = rename_rels(std::mem::take(&mut self.embedded), |rel| curies.expand(rel));
+
+        for embedded in self
+            .embedded
+            .values_mut()
+            .flat_map(SingleOrMultiple::iter_mut)
+        {
+            embedded.expand_curies_with(&curies);
+        }
+    }
+}
+
+impl<P> Serialize for Hal<P>
+where
+    P: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        HalRepr::new(self, None).serialize(serializer)
+    }
+}
+
+/// Representation of a HAL document as it is to be serialized.
+#[derive(Serialize)]
+struct HalRepr<'a, P> {
+    #[serde(rename = "_links", skip_serializing_if = "Links::is_empty")]
+    links: Links<'a>,
+
+    #[serde(rename = "_embedded", skip_serializing_if = "Embedded::is_empty")]
+    embedded: Embedded<'a>,
+
+    #[serde(rename = "_templates", skip_serializing_if = "BTreeMap::is_empty")]
+    templates: &'a BTreeMap<String, Template>,
+
+    #[serde(flatten)]
+    payload: &'a P,
+}
+
+/// The links of a HAL document to serialize - either exactly as they are, or with the link
+/// relations compacted into CURIE form.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Links<'a> {
+    Borrowed(&'a BTreeMap<String, SingleOrMultiple<Link>>),
+    Compacted(BTreeMap<String, SingleOrMultiple<&'a Link>>),
+}
+
+/// The embedded documents of a HAL document to serialize - either exactly as they are, or with the
+/// link relations compacted into CURIE form.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Embedded<'a> {
+    Borrowed(&'a BTreeMap<String, SingleOrMultiple<Hal<AnyPayload>>>),
+    Compacted(BTreeMap<String, SingleOrMultiple<HalRepr<'a, AnyPayload>>>),
+}
+
+impl<'a, P> HalRepr<'a, P> {
+    /// Build the representation of the given HAL document.
+    /// If CURIEs are inherited from a parent document then the link relations will be compacted
+    /// using them as well as any registered on this document.
+    fn new(hal: &'a Hal<P>, inherited: Option<&Curies>) -> Self {
+        let curies = match inherited {
+            Some(curies) => Some(curies.extend_from(&hal.links)),
+            None if hal.compact_curies => Some(Curies::default().extend_from(&hal.links)),
+            None => None,
+        };
+
+        let (links, embedded) = match curies {
+            None => (
+                Links::Borrowed(&hal.links),
+                Embedded::Borrowed(&hal.embedded),
+            ),
+            Some(curies) => {
+                let links = hal
+                    .links
+                    .iter()
+                    .map(|(rel, links)| (rel.clone(), links.map(|link| link)));
+                let embedded = hal.embedded.iter().map(|(rel, embedded)| {
+                    (
+                        rel.clone(),
+                        embedded.map(|embedded| HalRepr::new(embedded, Some(&curies))),
+                    )
+                });
+
+                (
+                    Links::Compacted(rename_rels(links, |rel| curies.compact(rel))),
+                    Embedded::Compacted(rename_rels(embedded, |rel| curies.compact(rel))),
+                )
+            },
+        };
+
+        Self {
+            links,
+            embedded,
+            templates: &hal.templates,
+            payload: &hal.payload,
+        }
+    }
+}
+
+impl Links<'_> {
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Borrowed(links) => links.is_empty(),
+            Self::Compacted(links) => links.is_empty(),
+        }
+    }
+}
+
+impl Embedded<'_> {
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Borrowed(embedded) => embedded.is_empty(),
+            Self::Compacted(embedded) => embedded.is_empty(),
+        }
+    }
 }
@@ -1,13 +1,20 @@
 mod link_hints;
+mod uri_template;
+
+use std::collections::BTreeMap;
 
 pub use link_hints::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+pub use uri_template::*;
+
+use crate::Error;
 
 /// Representation of a single Link in a HAL document.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Link {
     pub href:        String,
-    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default, skip_serializing_if = "is_false")]
     pub templated:   bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub r#type:      Option<String>,
@@ -23,6 +30,9 @@ pub struct Link {
     pub hreflang:    Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub hints:       Option<LinkHints>,
+    /// Any additional fields on the link that aren't otherwise known about.
+    #[serde(flatten)]
+    pub extensions:  BTreeMap<String, Value>,
 }
 
 impl Link {
@@ -42,6 +52,7 @@ impl Link {
             title:       None,
             hreflang:    None,
             hints:       None,
+            extensions:  BTreeMap::new(),
         }
     }
 
@@ -53,6 +64,16 @@ impl Link {
         self
     }
 
+    /// Indicate that the link is templated, checking that the href is a valid URI Template.
+    ///
+    /// # Errors
+    /// If the href is not a valid RFC 6570 URI Template.
+    pub fn try_templated(self) -> Result<Self, Error> {
+        UriTemplate::parse(&self.href)?;
+
+        Ok(self.templated())
+    }
+
     /// Specify the type value on the link.
     #[must_use]
     pub fn with_type<S>(mut self, value: S) -> Self
@@ -129,6 +150,46 @@ impl Link {
 
         self
     }
+
+    /// Get the names of the variables used in the href of a templated link.
+    /// A link that isn't templated has no variables.
+    ///
+    /// # Errors
+    /// If the link is templated but the href is not a valid RFC 6570 URI Template.
+    pub fn variables(&self) -> Result<Vec<String>, Error> {
+        if !self.templated {
+            return Ok(vec![]);
+        }
+
+        let template = UriTemplate::parse(&self.href)?;
+
+        Ok(template
+            .variables()
+            .into_iter()
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    /// Expand a templated link using the provided variables, producing a concrete link.
+    /// A link that isn't templated is returned unchanged.
+    ///
+    /// The variables can be anything that serializes into a JSON object, such as a map or a struct.
+    ///
+    /// # Errors
+    /// If the href is not a valid URI Template, or if the variables can't be used to expand it.
+    pub fn expand<V>(&self, variables: &V) -> Result<Self, Error>
+    where
+        V: Serialize + ?Sized,
+    {
+        let mut result = self.clone();
+
+        if self.templated {
+            result.href = UriTemplate::parse(&self.href)?.expand(variables)?;
+            result.templated = false;
+        }
+
+        Ok(result)
+    }
 }
 
 impl<S> From<S> for Link
@@ -144,3 +205,66 @@ where
 fn is_false(t: &bool) -> bool {
     t == &false
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use assert2::{check, let_assert};
+
+    use super::*;
+
+    #[test]
+    fn try_templated_valid() {
+        let result = Link::new("/orders{?page,size}").try_templated();
+        let_assert!(Ok(link) = result);
+
+        check!(link.templated);
+    }
+
+    #[test]
+    fn try_templated_invalid() {
+        let result = Link::new("/orders{?page,size").try_templated();
+        let_assert!(Err(Error::InvalidUriTemplate { template, .. }) = result);
+
+        check!(template == "/orders{?page,size");
+    }
+
+    #[test]
+    fn variables() {
+        let sut = Link::new("/orders/{id}{?page,size}").templated();
+
+        let_assert!(Ok(variables) = sut.variables());
+        check!(variables == vec!["id", "page", "size"]);
+    }
+
+    #[test]
+    fn variables_not_templated() {
+        let sut = Link::new("/orders/{id}");
+
+        let_assert!(Ok(variables) = sut.variables());
+        check!(variables.is_empty());
+    }
+
+    #[test]
+    fn expand() {
+        let sut = Link::new("/orders{?page,size}")
+            .templated()
+            .with_title("Orders");
+
+        let result = sut.expand(&HashMap::from([("page", 2)]));
+        let_assert!(Ok(link) = result);
+
+        check!(link == Link::new("/orders?page=2").with_title("Orders"));
+    }
+
+    #[test]
+    fn expand_not_templated() {
+        let sut = Link::new("/orders{?page,size}");
+
+        let result = sut.expand(&HashMap::from([("page", 2)]));
+        let_assert!(Ok(link) = result);
+
+        check!(link == sut);
+    }
+}
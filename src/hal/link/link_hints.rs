@@ -1,10 +1,11 @@
 use std::collections::BTreeMap;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Representation of Link Hints from draft-ietf-httpapi-link-hint-01
-#[derive(Debug, Serialize, Default)]
-#[serde(rename_all = "kebab-case")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default, rename_all = "kebab-case")]
 pub struct LinkHints {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub allow:            Vec<String>,
@@ -24,21 +25,31 @@ pub struct LinkHints {
     pub auth_schemes:     Vec<LinkHintAuthSchemes>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status:           Option<String>,
+    /// Any additional hints that aren't otherwise known about.
+    #[serde(flatten)]
+    pub extensions:       BTreeMap<String, Value>,
 }
 
 /// Representation of object for the "format" field of the Link Hints.
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct LinkHintFormat {
     #[serde(skip_serializing_if = "is_false")]
     pub deprecated: bool,
+    /// Any additional fields on the format that aren't otherwise known about.
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, Value>,
 }
 
 /// Representation of object for the "auth-schemes" field of the Link Hints.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LinkHintAuthSchemes {
-    pub scheme: String,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub realms: Vec<String>,
+    pub scheme:     String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub realms:     Vec<String>,
+    /// Any additional fields on the auth scheme that aren't otherwise known about.
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, Value>,
 }
 
 #[allow(clippy::trivially_copy_pass_by_ref)] // Needed for Serde.
@@ -189,8 +200,9 @@ impl LinkHintAuthSchemes {
         S: ToString,
     {
         Self {
-            scheme: scheme.to_string(),
-            realms: vec![],
+            scheme:     scheme.to_string(),
+            realms:     vec![],
+            extensions: BTreeMap::new(),
         }
     }
 
@@ -0,0 +1,762 @@
+use std::{fmt::Write, str::FromStr};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::Error;
+
+/// Representation of an RFC 6570 URI Template, supporting all expressions up to level 4.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UriTemplate {
+    template: String,
+    parts:    Vec<Part>,
+}
+
+/// A single part of a URI Template - either a literal string or an expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Literal(String),
+    Expression {
+        operator:  Operator,
+        variables: Vec<VarSpec>,
+    },
+}
+
+/// A single variable in an expression, along with any modifier to apply.
+#[derive(Debug, Clone, PartialEq)]
+struct VarSpec {
+    name:     String,
+    modifier: Modifier,
+}
+
+/// The modifier to apply to a variable when expanding it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Modifier {
+    None,
+    Prefix(usize),
+    Explode,
+}
+
+/// The operators that can be used in an expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Simple,
+    Reserved,
+    Fragment,
+    Label,
+    PathSegment,
+    PathParameter,
+    Query,
+    QueryContinuation,
+}
+
+/// A single value that a variable can be expanded with.
+enum VariableValue {
+    String(String),
+    List(Vec<String>),
+    Associative(Vec<(String, String)>),
+}
+
+impl Operator {
+    /// The string to output before the first expanded variable.
+    fn first(self) -> &'static str {
+        match self {
+            Self::Simple | Self::Reserved => "",
+            Self::Fragment => "#",
+            Self::Label => ".",
+            Self::PathSegment => "/",
+            Self::PathParameter => ";",
+            Self::Query => "?",
+            Self::QueryContinuation => "&",
+        }
+    }
+
+    /// The string to output between expanded variables.
+    fn separator(self) -> &'static str {
+        match self {
+            Self::Simple | Self::Reserved | Self::Fragment => ",",
+            Self::Label => ".",
+            Self::PathSegment => "/",
+            Self::PathParameter => ";",
+            Self::Query | Self::QueryContinuation => "&",
+        }
+    }
+
+    /// Whether the variables are output as name/value pairs.
+    fn named(self) -> bool {
+        matches!(
+            self,
+            Self::PathParameter | Self::Query | Self::QueryContinuation
+        )
+    }
+
+    /// The string to output after the name of a named variable whose value is empty.
+    fn if_empty(self) -> &'static str {
+        match self {
+            Self::Query | Self::QueryContinuation => "=",
+            _ => "",
+        }
+    }
+
+    /// Whether reserved characters are allowed through without being encoded.
+    fn allow_reserved(self) -> bool {
+        matches!(self, Self::Reserved | Self::Fragment)
+    }
+}
+
+impl UriTemplate {
+    /// Parse the given string as a URI Template.
+    ///
+    /// # Errors
+    /// If the string is not a valid URI Template.
+    pub fn parse<S>(template: S) -> Result<Self, Error>
+    where
+        S: ToString,
+    {
+        let template = template.to_string();
+        let invalid = |reason| Error::InvalidUriTemplate {
+            template: template.clone(),
+            reason,
+        };
+
+        let mut parts = vec![];
+        let mut remaining = template.as_str();
+
+        while !remaining.is_empty() {
+            if let Some(expression) = remaining.strip_prefix('{') {
+                let end = expression
+                    .find('}')
+                    .ok_or_else(|| invalid("unterminated expression"))?;
+
+                parts.push(parse_expression(&expression[..end]).map_err(invalid)?);
+                remaining = &expression[end + 1..];
+            } else {
+                let end = remaining.find('{').unwrap_or(remaining.len());
+
+                parts.push(Part::Literal(
+                    parse_literal(&remaining[..end]).map_err(invalid)?,
+                ));
+                remaining = &remaining[end..];
+            }
+        }
+
+        Ok(Self { template, parts })
+    }
+
+    /// Get the names of all the variables used in the template, in the order they first appear.
+    #[must_use]
+    pub fn variables(&self) -> Vec<&str> {
+        let mut result: Vec<&str> = vec![];
+
+        for part in &self.parts {
+            if let Part::Expression { variables, .. } = part {
+                for variable in variables {
+                    if !result.contains(&variable.name.as_str()) {
+                        result.push(&variable.name);
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Whether every expression in the template is a query expression, so that the template is a
+    /// fixed URI with optional query parameters.
+    pub(crate) fn is_query(&self) -> bool {
+        let mut expressions = self.parts.iter().filter_map(|part| match part {
+            Part::Literal(_) => None,
+            Part::Expression { operator, .. } => Some(*operator),
+        });
+
+        expressions.clone().next().is_some()
+            && expressions
+                .all(|operator| matches!(operator, Operator::Query | Operator::QueryContinuation))
+    }
+
+    /// Expand the template using the given variables.
+    ///
+    /// The variables can be anything that serializes into a JSON object - for example a `HashMap`
+    /// or a struct deriving `Serialize`. Strings, numbers and booleans are used as simple values,
+    /// arrays as lists and objects as associative arrays. Nulls are treated as undefined.
+    ///
+    /// # Errors
+    /// If the variables can not be serialized into a JSON object, or if any of the values can not
+    /// be used in the template.
+    pub fn expand<V>(&self, variables: &V) -> Result<String, Error>
+    where
+        V: Serialize + ?Sized,
+    {
+        let variables = match serde_json::to_value(variables).map_err(Error::Serialization)? {
+            Value::Object(variables) => variables,
+            Value::Null => Map::new(),
+            _ => return Err(Error::NotAnObject),
+        };
+
+        let mut result = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => result.push_str(literal),
+                Part::Expression {
+                    operator,
+                    variables: specs,
+                } => expand_expression(&mut result, *operator, specs, &variables)?,
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+impl FromStr for UriTemplate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for UriTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+/// Parse a literal section of a template, encoding any characters that need it.
+fn parse_literal(literal: &str) -> Result<String, &'static str> {
+    let mut result = String::new();
+    let mut chars = literal.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '%' => {
+                let encoded = literal
+                    .get(index..index + 3)
+                    .filter(|encoded| is_pct_encoded(encoded))
+                    .ok_or("invalid percent-encoding")?;
+                result.push_str(encoded);
+                chars.nth(1);
+            },
+            '\0'..=' ' | '\x7f' | '"' | '\'' | '<' | '>' | '\\' | '^' | '`' | '|' | '}' => {
+                return Err("invalid character in literal");
+            },
+            c => encode_into(&mut result, &c.to_string(), true),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Parse the contents of a single expression.
+fn parse_expression(expression: &str) -> Result<Part, &'static str> {
+    let (operator, variables) = match expression.chars().next() {
+        Some('+') => (Operator::Reserved, &expression[1..]),
+        Some('#') => (Operator::Fragment, &expression[1..]),
+        Some('.') => (Operator::Label, &expression[1..]),
+        Some('/') => (Operator::PathSegment, &expression[1..]),
+        Some(';') => (Operator::PathParameter, &expression[1..]),
+        Some('?') => (Operator::Query, &expression[1..]),
+        Some('&') => (Operator::QueryContinuation, &expression[1..]),
+        Some('=' | ',' | '!' | '@' | '|') => return Err("reserved operator"),
+        Some(_) => (Operator::Simple, expression),
+        None => return Err("empty expression"),
+    };
+
+    let variables = variables
+        .split(',')
+        .map(parse_varspec)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Part::Expression {
+        operator,
+        variables,
+    })
+}
+
+/// Parse a single variable specification from an expression.
+fn parse_varspec(varspec: &str) -> Result<VarSpec, &'static str> {
+    let (name, modifier) = if let Some(name) = varspec.strip_suffix('*') {
+        (name, Modifier::Explode)
+    } else if let Some((name, length)) = varspec.split_once(':') {
+        let valid_length = !length.is_empty()
+            && length.len() <= 4
+            && !length.starts_with('0')
+            && length.chars().all(|c| c.is_ascii_digit());
+        if !valid_length {
+            return Err("invalid prefix modifier");
+        }
+
+        (
+            name,
+            Modifier::Prefix(length.parse().map_err(|_| "invalid prefix modifier")?),
+        )
+    } else {
+        (varspec, Modifier::None)
+    };
+
+    let valid_name = !name.is_empty()
+        && !name.starts_with('.')
+        && !name.ends_with('.')
+        && !name.contains("..")
+        && name.char_indices().all(|(index, c)| {
+            c.is_ascii_alphanumeric()
+                || c == '_'
+                || c == '.'
+                || (c == '%' && name.get(index..index + 3).is_some_and(is_pct_encoded))
+        });
+    if !valid_name {
+        return Err("invalid variable name");
+    }
+
+    Ok(VarSpec {
+        name: name.to_string(),
+        modifier,
+    })
+}
+
+/// Expand a single expression into the result.
+fn expand_expression(
+    result: &mut String,
+    operator: Operator,
+    specs: &[VarSpec],
+    variables: &Map<String, Value>,
+) -> Result<(), Error> {
+    let allow_reserved = operator.allow_reserved();
+    let mut first = true;
+
+    for spec in specs {
+        let Some(value) = variable_value(&spec.name, variables.get(&spec.name))? else {
+            continue;
+        };
+
+        result.push_str(if first {
+            operator.first()
+        } else {
+            operator.separator()
+        });
+        first = false;
+
+        match (value, spec.modifier) {
+            (VariableValue::String(value), modifier) => {
+                if operator.named() {
+                    result.push_str(&spec.name);
+                    if value.is_empty() {
+                        result.push_str(operator.if_empty());
+                        continue;
+                    }
+                    result.push('=');
+                }
+
+                let value = match modifier {
+                    Modifier::Prefix(length) => value.chars().take(length).collect(),
+                    _ => value,
+                };
+                encode_into(result, &value, allow_reserved);
+            },
+            (_, Modifier::Prefix(_)) => {
+                return Err(Error::InvalidUriTemplateVariable(spec.name.clone()));
+            },
+            (VariableValue::List(values), Modifier::None) => {
+                if operator.named() {
+                    result.push_str(&spec.name);
+                    result.push('=');
+                }
+
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        result.push(',');
+                    }
+                    encode_into(result, value, allow_reserved);
+                }
+            },
+            (VariableValue::Associative(values), Modifier::None) => {
+                if operator.named() {
+                    result.push_str(&spec.name);
+                    result.push('=');
+                }
+
+                for (index, (key, value)) in values.iter().enumerate() {
+                    if index > 0 {
+                        result.push(',');
+                    }
+                    encode_into(result, key, allow_reserved);
+                    result.push(',');
+                    encode_into(result, value, allow_reserved);
+                }
+            },
+            (VariableValue::List(values), Modifier::Explode) => {
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        result.push_str(operator.separator());
+                    }
+                    if operator.named() {
+                        result.push_str(&spec.name);
+                        if value.is_empty() {
+                            result.push_str(operator.if_empty());
+                            continue;
+                        }
+                        result.push('=');
+                    }
+                    encode_into(result, value, allow_reserved);
+                }
+            },
+            (VariableValue::Associative(values), Modifier::Explode) => {
+                for (index, (key, value)) in values.iter().enumerate() {
+                    if index > 0 {
+                        result.push_str(operator.separator());
+                    }
+                    encode_into(result, key, allow_reserved);
+                    if operator.named() && value.is_empty() {
+                        result.push_str(operator.if_empty());
+                    } else {
+                        result.push('=');
+                        encode_into(result, value, allow_reserved);
+                    }
+                }
+            },
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a JSON value into the value to use for a variable.
+/// Returns `None` if the variable is to be considered undefined.
+fn variable_value(name: &str, value: Option<&Value>) -> Result<Option<VariableValue>, Error> {
+    let scalar = |value: &Value| match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Number(value) => Ok(value.to_string()),
+        Value::Bool(value) => Ok(value.to_string()),
+        _ => Err(Error::InvalidUriTemplateVariable(name.to_string())),
+    };
+
+    let result = match value {
+        None | Some(Value::Null) => None,
+        Some(Value::Array(values)) if values.is_empty() => None,
+        Some(Value::Object(values)) if values.is_empty() => None,
+        Some(Value::Array(values)) => Some(VariableValue::List(
+            values.iter().map(scalar).collect::<Result<_, _>>()?,
+        )),
+        Some(Value::Object(values)) => Some(VariableValue::Associative(
+            values
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), scalar(value)?)))
+                .collect::<Result<_, Error>>()?,
+        )),
+        Some(value) => Some(VariableValue::String(scalar(value)?)),
+    };
+
+    Ok(result)
+}
+
+/// Percent-encode the given value into the result.
+/// If `allow_reserved` is set then reserved characters and existing percent-encoded triplets are
+/// passed through unchanged.
+fn encode_into(result: &mut String, value: &str, allow_reserved: bool) {
+    for (index, c) in value.char_indices() {
+        let passthrough = is_unreserved(c)
+            || (allow_reserved
+                && (is_reserved(c)
+                    || (c == '%' && value.get(index..index + 3).is_some_and(is_pct_encoded))));
+
+        if passthrough {
+            result.push(c);
+        } else {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                // Writing to a String can never fail.
+                let _ = write!(result, "%{byte:02X}");
+            }
+        }
+    }
+}
+
+/// Check if the character is in the "unreserved" set from RFC 3986.
+fn is_unreserved(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~')
+}
+
+/// Check if the character is in the "reserved" set from RFC 3986.
+fn is_reserved(c: char) -> bool {
+    matches!(
+        c,
+        ':' | '/'
+            | '?'
+            | '#'
+            | '['
+            | ']'
+            | '@'
+            | '!'
+            | '$'
+            | '&'
+            | '\''
+            | '('
+            | ')'
+            | '*'
+            | '+'
+            | ','
+            | ';'
+            | '='
+    )
+}
+
+/// Check if the string is a single percent-encoded triplet.
+fn is_pct_encoded(value: &str) -> bool {
+    let bytes = value.as_bytes();
+
+    bytes.len() == 3
+        && bytes[0] == b'%'
+        && bytes[1].is_ascii_hexdigit()
+        && bytes[2].is_ascii_hexdigit()
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+    use serde_json::json;
+
+    use super::*;
+
+    /// The example variables from RFC 6570 Section 3.2.
+    fn example_variables() -> Value {
+        json!({
+            "count": ["one", "two", "three"],
+            "dom": ["example", "com"],
+            "dub": "me/too",
+            "hello": "Hello World!",
+            "half": "50%",
+            "var": "value",
+            "who": "fred",
+            "base": "http://example.com/home/",
+            "path": "/foo/bar",
+            "list": ["red", "green", "blue"],
+            "keys": {"semi": ";", "dot": ".", "comma": ","},
+            "v": "6",
+            "x": "1024",
+            "y": "768",
+            "empty": "",
+            "empty_keys": {},
+            "undef": null
+        })
+    }
+
+    fn expand(template: &str) -> String {
+        let_assert!(Ok(template) = UriTemplate::parse(template));
+        let_assert!(Ok(result) = template.expand(&example_variables()));
+
+        result
+    }
+
+    #[test]
+    fn level_1() {
+        check!(expand("{var}") == "value");
+        check!(expand("{hello}") == "Hello%20World%21");
+        check!(expand("{half}") == "50%25");
+        check!(expand("O{empty}X") == "OX");
+        check!(expand("O{undef}X") == "OX");
+        check!(expand("{x,y}") == "1024,768");
+        check!(expand("{x,hello,y}") == "1024,Hello%20World%21,768");
+        check!(expand("?{x,empty}") == "?1024,");
+        check!(expand("?{x,undef}") == "?1024");
+        check!(expand("?{undef,y}") == "?768");
+        check!(expand("{var:3}") == "val");
+        check!(expand("{var:30}") == "value");
+        check!(expand("{list}") == "red,green,blue");
+        check!(expand("{list*}") == "red,green,blue");
+        check!(expand("{keys}") == "semi,%3B,dot,.,comma,%2C");
+        check!(expand("{keys*}") == "semi=%3B,dot=.,comma=%2C");
+    }
+
+    #[test]
+    fn reserved_expansion() {
+        check!(expand("{+var}") == "value");
+        check!(expand("{+hello}") == "Hello%20World!");
+        check!(expand("{+half}") == "50%25");
+        check!(expand("{base}index") == "http%3A%2F%2Fexample.com%2Fhome%2Findex");
+        check!(expand("{+base}index") == "http://example.com/home/index");
+        check!(expand("O{+empty}X") == "OX");
+        check!(expand("{+path}/here") == "/foo/bar/here");
+        check!(expand("here?ref={+path}") == "here?ref=/foo/bar");
+        check!(expand("up{+path}{var}/here") == "up/foo/barvalue/here");
+        check!(expand("{+x,hello,y}") == "1024,Hello%20World!,768");
+        check!(expand("{+path,x}/here") == "/foo/bar,1024/here");
+        check!(expand("{+path:6}/here") == "/foo/b/here");
+        check!(expand("{+list}") == "red,green,blue");
+        check!(expand("{+keys}") == "semi,;,dot,.,comma,,");
+        check!(expand("{+keys*}") == "semi=;,dot=.,comma=,");
+    }
+
+    #[test]
+    fn fragment_expansion() {
+        check!(expand("{#var}") == "#value");
+        check!(expand("{#hello}") == "#Hello%20World!");
+        check!(expand("{#half}") == "#50%25");
+        check!(expand("foo{#empty}") == "foo#");
+        check!(expand("foo{#undef}") == "foo");
+        check!(expand("X{#var}") == "X#value");
+        check!(expand("X{#hello}") == "X#Hello%20World!");
+        check!(expand("{#x,hello,y}") == "#1024,Hello%20World!,768");
+        check!(expand("{#path,x}/here") == "#/foo/bar,1024/here");
+        check!(expand("{#path:6}/here") == "#/foo/b/here");
+        check!(expand("{#list}") == "#red,green,blue");
+        check!(expand("{#list*}") == "#red,green,blue");
+        check!(expand("{#keys}") == "#semi,;,dot,.,comma,,");
+        check!(expand("{#keys*}") == "#semi=;,dot=.,comma=,");
+    }
+
+    #[test]
+    fn label_expansion() {
+        check!(expand("{.who}") == ".fred");
+        check!(expand("{.who,who}") == ".fred.fred");
+        check!(expand("{.half,who}") == ".50%25.fred");
+        check!(expand("www{.dom*}") == "www.example.com");
+        check!(expand("X{.var}") == "X.value");
+        check!(expand("X{.empty}") == "X.");
+        check!(expand("X{.undef}") == "X");
+        check!(expand("X{.var:3}") == "X.val");
+        check!(expand("X{.list}") == "X.red,green,blue");
+        check!(expand("X{.list*}") == "X.red.green.blue");
+        check!(expand("X{.keys}") == "X.semi,%3B,dot,.,comma,%2C");
+        check!(expand("X{.keys*}") == "X.semi=%3B.dot=..comma=%2C");
+        check!(expand("X{.empty_keys}") == "X");
+        check!(expand("X{.empty_keys*}") == "X");
+    }
+
+    #[test]
+    fn path_segment_expansion() {
+        check!(expand("{/who}") == "/fred");
+        check!(expand("{/who,who}") == "/fred/fred");
+        check!(expand("{/half,who}") == "/50%25/fred");
+        check!(expand("{/who,dub}") == "/fred/me%2Ftoo");
+        check!(expand("{/var}") == "/value");
+        check!(expand("{/var,empty}") == "/value/");
+        check!(expand("{/var,undef}") == "/value");
+        check!(expand("{/var,x}/here") == "/value/1024/here");
+        check!(expand("{/var:1,var}") == "/v/value");
+        check!(expand("{/list}") == "/red,green,blue");
+        check!(expand("{/list*}") == "/red/green/blue");
+        check!(expand("{/list*,path:4}") == "/red/green/blue/%2Ffoo");
+        check!(expand("{/keys}") == "/semi,%3B,dot,.,comma,%2C");
+        check!(expand("{/keys*}") == "/semi=%3B/dot=./comma=%2C");
+    }
+
+    #[test]
+    fn path_parameter_expansion() {
+        check!(expand("{;who}") == ";who=fred");
+        check!(expand("{;half}") == ";half=50%25");
+        check!(expand("{;empty}") == ";empty");
+        check!(expand("{;v,empty,who}") == ";v=6;empty;who=fred");
+        check!(expand("{;v,bar,who}") == ";v=6;who=fred");
+        check!(expand("{;x,y}") == ";x=1024;y=768");
+        check!(expand("{;x,y,empty}") == ";x=1024;y=768;empty");
+        check!(expand("{;x,y,undef}") == ";x=1024;y=768");
+        check!(expand("{;hello:5}") == ";hello=Hello");
+        check!(expand("{;list}") == ";list=red,green,blue");
+        check!(expand("{;list*}") == ";list=red;list=green;list=blue");
+        check!(expand("{;keys}") == ";keys=semi,%3B,dot,.,comma,%2C");
+        check!(expand("{;keys*}") == ";semi=%3B;dot=.;comma=%2C");
+    }
+
+    #[test]
+    fn query_expansion() {
+        check!(expand("{?who}") == "?who=fred");
+        check!(expand("{?half}") == "?half=50%25");
+        check!(expand("{?x,y}") == "?x=1024&y=768");
+        check!(expand("{?x,y,empty}") == "?x=1024&y=768&empty=");
+        check!(expand("{?x,y,undef}") == "?x=1024&y=768");
+        check!(expand("{?var:3}") == "?var=val");
+        check!(expand("{?list}") == "?list=red,green,blue");
+        check!(expand("{?list*}") == "?list=red&list=green&list=blue");
+        check!(expand("{?keys}") == "?keys=semi,%3B,dot,.,comma,%2C");
+        check!(expand("{?keys*}") == "?semi=%3B&dot=.&comma=%2C");
+    }
+
+    #[test]
+    fn query_continuation_expansion() {
+        check!(expand("{&who}") == "&who=fred");
+        check!(expand("{&half}") == "&half=50%25");
+        check!(expand("?fixed=yes{&x}") == "?fixed=yes&x=1024");
+        check!(expand("{&x,y,empty}") == "&x=1024&y=768&empty=");
+        check!(expand("{&var:3}") == "&var=val");
+        check!(expand("{&list}") == "&list=red,green,blue");
+        check!(expand("{&list*}") == "&list=red&list=green&list=blue");
+        check!(expand("{&keys}") == "&keys=semi,%3B,dot,.,comma,%2C");
+        check!(expand("{&keys*}") == "&semi=%3B&dot=.&comma=%2C");
+    }
+
+    #[test]
+    fn expand_struct() {
+        #[derive(Serialize)]
+        struct Search {
+            page: u32,
+            size: Option<u32>,
+            sort: Vec<&'static str>,
+        }
+
+        let_assert!(Ok(template) = UriTemplate::parse("/orders{?page,size,sort*}"));
+        let result = template.expand(&Search {
+            page: 2,
+            size: None,
+            sort: vec!["name", "date"],
+        });
+
+        let_assert!(Ok(result) = result);
+        check!(result == "/orders?page=2&sort=name&sort=date");
+    }
+
+    #[test]
+    fn literals_are_encoded() {
+        check!(expand("/caf\u{e9}/{var}") == "/caf%C3%A9/value");
+        check!(expand("/a%20b") == "/a%20b");
+    }
+
+    #[test]
+    fn variables() {
+        let_assert!(Ok(template) = UriTemplate::parse("/orders{/id}{?page,size}{&page,filter*}"));
+
+        check!(template.variables() == vec!["id", "page", "size", "filter"]);
+    }
+
+    #[test]
+    fn invalid_templates() {
+        for template in [
+            "/orders{",
+            "/orders{}",
+            "/orders}",
+            "/orders{=id}",
+            "/orders{|id}",
+            "/orders{id:0}",
+            "/orders{id:10000}",
+            "/orders{id:}",
+            "/orders{.id.}",
+            "/orders{id-x}",
+            "/orders{id,}",
+            "/orders%2",
+            "/orders with space",
+        ] {
+            let_assert!(
+                Err(Error::InvalidUriTemplate { .. }) = UriTemplate::parse(template),
+                "{template}"
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_values() {
+        let_assert!(Ok(template) = UriTemplate::parse("{list:3}"));
+        let_assert!(
+            Err(Error::InvalidUriTemplateVariable(name)) =
+                template.expand(&json!({"list": ["a", "b"]}))
+        );
+        check!(name == "list");
+
+        let_assert!(Ok(template) = UriTemplate::parse("{nested}"));
+        let_assert!(
+            Err(Error::InvalidUriTemplateVariable(_)) =
+                template.expand(&json!({"nested": [["a"]]}))
+        );
+
+        let_assert!(Err(Error::NotAnObject) = template.expand(&42));
+    }
+}
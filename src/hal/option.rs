@@ -1,10 +1,14 @@
-use serde::Serialize;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::Link;
 
 /// Representation of the options for a single template property.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)] // Boxing the link would make the public API more awkward.
 pub enum TemplateOptions {
     Inline {
         inline: Vec<InlineOption>,
@@ -15,8 +19,12 @@ pub enum TemplateOptions {
         #[serde(rename = "minItems", skip_serializing_if = "Option::is_none")]
         min_items: Option<u32>,
 
+        #[serde(default)]
         #[serde(rename = "selectedValues", skip_serializing_if = "Vec::is_empty")]
         selected_values: Vec<String>,
+
+        #[serde(flatten)]
+        extensions: BTreeMap<String, Value>,
     },
     Link {
         link: Link,
@@ -27,18 +35,58 @@ pub enum TemplateOptions {
         #[serde(rename = "minItems", skip_serializing_if = "Option::is_none")]
         min_items: Option<u32>,
 
+        #[serde(default)]
         #[serde(rename = "selectedValues", skip_serializing_if = "Vec::is_empty")]
         selected_values: Vec<String>,
+
+        #[serde(flatten)]
+        extensions: BTreeMap<String, Value>,
     },
 }
 
 /// Representation of a single option for a single template property.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "InlineOptionRepr")]
 pub struct InlineOption {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt: Option<String>,
 
     pub value: String,
+
+    /// Any additional fields on the option that aren't otherwise known about.
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, Value>,
+}
+
+/// The forms that an inline option can take in a HAL-FORMS document - either a bare string or an
+/// object containing the value and an optional prompt.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum InlineOptionRepr {
+    Value(String),
+    Option {
+        prompt:     Option<String>,
+        value:      String,
+        #[serde(flatten)]
+        extensions: BTreeMap<String, Value>,
+    },
+}
+
+impl From<InlineOptionRepr> for InlineOption {
+    fn from(value: InlineOptionRepr) -> Self {
+        match value {
+            InlineOptionRepr::Value(value) => Self::new(value),
+            InlineOptionRepr::Option {
+                prompt,
+                value,
+                extensions,
+            } => Self {
+                prompt,
+                value,
+                extensions,
+            },
+        }
+    }
 }
 
 impl TemplateOptions {
@@ -52,6 +100,7 @@ impl TemplateOptions {
             max_items:       None,
             min_items:       None,
             selected_values: vec![],
+            extensions:      BTreeMap::new(),
         }
     }
 
@@ -65,6 +114,7 @@ impl TemplateOptions {
             max_items:       None,
             min_items:       None,
             selected_values: vec![],
+            extensions:      BTreeMap::new(),
         }
     }
 
@@ -79,22 +129,26 @@ impl TemplateOptions {
                 max_items: _,
                 min_items,
                 selected_values,
+                extensions,
             } => Self::Inline {
                 inline,
                 max_items: Some(value.into()),
                 min_items,
                 selected_values,
+                extensions,
             },
             Self::Link {
                 link,
                 max_items: _,
                 min_items,
                 selected_values,
+                extensions,
             } => Self::Link {
                 link,
                 max_items: Some(value.into()),
                 min_items,
                 selected_values,
+                extensions,
             },
         }
     }
@@ -110,22 +164,26 @@ impl TemplateOptions {
                 max_items,
                 min_items: _,
                 selected_values,
+                extensions,
             } => Self::Inline {
                 inline,
                 max_items,
                 min_items: Some(value.into()),
                 selected_values,
+                extensions,
             },
             Self::Link {
                 link,
                 max_items,
                 min_items: _,
                 selected_values,
+                extensions,
             } => Self::Link {
                 link,
                 max_items,
                 min_items: Some(value.into()),
                 selected_values,
+                extensions,
             },
         }
     }
@@ -141,6 +199,7 @@ impl TemplateOptions {
                 max_items,
                 min_items,
                 mut selected_values,
+                extensions,
             } => {
                 selected_values.push(value.to_string());
                 Self::Inline {
@@ -148,6 +207,7 @@ impl TemplateOptions {
                     max_items,
                     min_items,
                     selected_values,
+                    extensions,
                 }
             },
             Self::Link {
@@ -155,6 +215,7 @@ impl TemplateOptions {
                 max_items,
                 min_items,
                 mut selected_values,
+                extensions,
             } => {
                 selected_values.push(value.to_string());
                 Self::Link {
@@ -162,6 +223,7 @@ impl TemplateOptions {
                     max_items,
                     min_items,
                     selected_values,
+                    extensions,
                 }
             },
         }
@@ -175,8 +237,9 @@ impl InlineOption {
         V: ToString,
     {
         Self {
-            prompt: None,
-            value:  value.to_string(),
+            prompt:     None,
+            value:      value.to_string(),
+            extensions: BTreeMap::new(),
         }
     }
 
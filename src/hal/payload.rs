@@ -0,0 +1,91 @@
+use std::{any::Any, fmt::Debug};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Type-erased payload for a HAL document.
+///
+/// This is used for embedded documents, so that a document can embed other documents with any
+/// payload type. The payload is only serialized when the containing document is.
+pub struct AnyPayload(Box<dyn ErasedPayload>);
+
+/// Object-safe trait for anything that can be used as the payload of an embedded document.
+trait ErasedPayload: erased_serde::Serialize + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T> ErasedPayload for T
+where
+    T: Serialize + Send + Sync + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+erased_serde::serialize_trait_object!(ErasedPayload);
+
+impl AnyPayload {
+    /// Wrap up the given payload value.
+    #[must_use]
+    pub fn new<T>(value: T) -> Self
+    where
+        T: Serialize + Send + Sync + 'static,
+    {
+        Self(Box::new(value))
+    }
+
+    /// Get the payload as the given type, if that is the type that it was created with.
+    ///
+    /// Payloads of parsed documents are always `serde_json::Value`s.
+    #[must_use]
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: 'static,
+    {
+        let value = (*self.0).as_any();
+
+        // Embedding an already embedded document will wrap the payload twice, so look through it.
+        value
+            .downcast_ref()
+            .or_else(|| value.downcast_ref::<Self>().and_then(Self::downcast_ref))
+    }
+
+    /// Serialize the payload into a `serde_json::Value`.
+    ///
+    /// # Errors
+    /// If the payload can not be serialized into JSON for some reason.
+    pub fn to_value(&self) -> Result<Value, serde_json::Error> {
+        match self.downcast_ref::<Value>() {
+            Some(value) => Ok(value.clone()),
+            None => serde_json::to_value(self),
+        }
+    }
+}
+
+impl Debug for AnyPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_value() {
+            Ok(value) => f.debug_tuple("AnyPayload").field(&value).finish(),
+            Err(_) => f.write_str("AnyPayload(..)"),
+        }
+    }
+}
+
+impl Serialize for AnyPayload {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        erased_serde::serialize(&*self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AnyPayload {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Value::deserialize(deserializer).map(Self::new)
+    }
+}
@@ -1,25 +1,28 @@
-use serde::Serialize;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 use crate::TemplateOptions;
 
 /// Representation of a single Property in a HAL-FORMS Template.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TemplateProperty {
     pub name: String,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt: Option<String>,
 
-    #[serde(rename = "readOnly", skip_serializing_if = "is_false")]
+    #[serde(rename = "readOnly", default, skip_serializing_if = "is_false")]
     pub readonly: bool,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub regex: Option<String>,
 
-    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default, skip_serializing_if = "is_false")]
     pub required: bool,
 
-    #[serde(skip_serializing_if = "is_false")]
+    #[serde(default, skip_serializing_if = "is_false")]
     pub templated: bool,
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -54,6 +57,10 @@ pub struct TemplateProperty {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub r#type: Option<String>,
+
+    /// Any additional fields on the property that aren't otherwise known about.
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, Value>,
 }
 
 impl TemplateProperty {
@@ -80,6 +87,7 @@ impl TemplateProperty {
             rows:        None,
             step:        None,
             r#type:      None,
+            extensions:  BTreeMap::new(),
         }
     }
 
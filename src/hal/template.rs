@@ -1,9 +1,16 @@
-use serde::Serialize;
+mod validation;
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+pub use validation::*;
 
 use crate::TemplateProperty;
 
 /// Representation of a single Template in a HAL-FORMS document.
-#[derive(Debug, Serialize, Default)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct Template {
     #[serde(rename = "contentType", skip_serializing_if = "Option::is_none")]
     pub content_type: Option<String>,
@@ -19,6 +26,10 @@ pub struct Template {
 
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub properties: Vec<TemplateProperty>,
+
+    /// Any additional fields on the template that aren't otherwise known about.
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, Value>,
 }
 
 impl Template {
@@ -72,3 +83,210 @@ impl Template {
         self
     }
 }
+
+/// Trait for types that are submitted to the server using a HAL-FORMS template, allowing the
+/// template to be produced from the type itself.
+///
+/// This can be implemented using `#[derive(HalTemplate)]`, which requires the `derive` feature.
+pub trait HalTemplate {
+    /// The name that the template is given within the `_templates` of a HAL-FORMS document.
+    const NAME: &'static str = "default";
+
+    /// Produce the template for submitting this type.
+    fn template() -> Template;
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::check;
+    use http_halforms_derive::HalTemplate;
+    use insta::assert_json_snapshot;
+    use serde::{Deserialize, Deserializer};
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "snake_case")]
+    #[allow(dead_code)]
+    enum Role {
+        Admin,
+        Member,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Tag {
+        Red,
+        Blue,
+    }
+
+    #[derive(Deserialize, HalTemplate)]
+    #[allow(dead_code)]
+    struct Address {
+        street: String,
+        #[serde(default)]
+        city:   String,
+    }
+
+    #[derive(Deserialize, HalTemplate)]
+    #[hal_template(name = "create", method = POST, target = "/users", title = "Create User")]
+    #[serde(rename_all = "camelCase")]
+    #[allow(dead_code)]
+    struct CreateUser {
+        #[hal_template(prompt = "Display Name", min_length = 1, max_length = 50)]
+        display_name: String,
+        #[hal_template(type = Email, placeholder = "user@example.com")]
+        email:        Option<String>,
+        #[serde(rename = "yearsOld")]
+        #[hal_template(type = Number, min = 18, max = 150)]
+        age:          u32,
+        #[hal_template(regex = "^[a-z]+$", required = false)]
+        r#ref:        String,
+        role:         Role,
+        tags:         Vec<Tag>,
+        #[serde(skip)]
+        internal:     String,
+        #[hal_template(skip)]
+        ignored:      Option<String>,
+        #[serde(flatten)]
+        address:      Option<Address>,
+    }
+
+    #[derive(Default, Deserialize, HalTemplate)]
+    #[hal_template(content_type = Form, method = "PATCH")]
+    #[serde(default)]
+    #[allow(dead_code)]
+    struct Search<'a> {
+        #[hal_template(readonly, type = "search")]
+        query: &'a str,
+    }
+
+    /// A field type that can only be deserialized with a custom function.
+    #[derive(Default)]
+    struct Opaque;
+
+    fn opaque<'de, D>(deserializer: D) -> Result<Opaque, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(|_| Opaque)
+    }
+
+    #[derive(Deserialize, HalTemplate)]
+    #[allow(dead_code)]
+    struct Session {
+        #[serde(deserialize_with = "opaque")]
+        token: Opaque,
+        #[serde(skip_deserializing)]
+        cache: Opaque,
+        name:  String,
+    }
+
+    #[test]
+    fn derive_template() {
+        check!(CreateUser::NAME == "create");
+        assert_json_snapshot!(CreateUser::template(), @r###"
+        {
+          "method": "POST",
+          "target": "/users",
+          "title": "Create User",
+          "properties": [
+            {
+              "name": "displayName",
+              "prompt": "Display Name",
+              "required": true,
+              "maxLength": 50,
+              "minLength": 1
+            },
+            {
+              "name": "email",
+              "placeholder": "user@example.com",
+              "type": "email"
+            },
+            {
+              "name": "yearsOld",
+              "required": true,
+              "max": 150,
+              "min": 18,
+              "type": "number"
+            },
+            {
+              "name": "ref",
+              "regex": "^[a-z]+$"
+            },
+            {
+              "name": "role",
+              "required": true,
+              "options": {
+                "inline": [
+                  {
+                    "value": "admin"
+                  },
+                  {
+                    "value": "member"
+                  }
+                ],
+                "maxItems": 1
+              }
+            },
+            {
+              "name": "tags",
+              "required": true,
+              "options": {
+                "inline": [
+                  {
+                    "value": "Red"
+                  },
+                  {
+                    "value": "Blue"
+                  }
+                ]
+              }
+            },
+            {
+              "name": "street"
+            },
+            {
+              "name": "city"
+            }
+          ]
+        }
+        "###);
+    }
+
+    #[test]
+    fn derive_custom_deserialize() {
+        assert_json_snapshot!(Session::template(), @r###"
+        {
+          "properties": [
+            {
+              "name": "token",
+              "required": true
+            },
+            {
+              "name": "name",
+              "required": true
+            }
+          ]
+        }
+        "###);
+    }
+
+    #[test]
+    fn derive_default_template() {
+        check!(Search::NAME == "default");
+        assert_json_snapshot!(Search::template(), @r###"
+        {
+          "contentType": "application/x-www-form-urlencoded",
+          "method": "PATCH",
+          "properties": [
+            {
+              "name": "query",
+              "readOnly": true,
+              "type": "search"
+            }
+          ]
+        }
+        "###);
+    }
+}
@@ -0,0 +1,675 @@
+use std::collections::BTreeMap;
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{Error, Template, TemplateOptions, TemplateProperty};
+
+/// The ways in which a single value can fail validation against a template property.
+#[derive(Debug, Clone, PartialEq, Serialize, derive_more::Display)]
+#[serde(tag = "violation", rename_all = "camelCase")]
+pub enum Violation {
+    /// The property is required but no value was provided.
+    #[display("is required")]
+    Required,
+
+    /// The property is read-only but a different value was provided.
+    #[display("is read-only")]
+    ReadOnly,
+
+    /// The value doesn't match the regex for the property.
+    #[display("must match {regex}")]
+    Regex { regex: String },
+
+    /// The value is lower than the minimum for the property.
+    #[display("must be at least {min}")]
+    Min { min: u32 },
+
+    /// The value is higher than the maximum for the property.
+    #[display("must be at most {max}")]
+    Max { max: u32 },
+
+    /// The value is shorter than the minimum length for the property.
+    #[display("must be at least {min_length} characters")]
+    #[serde(rename_all = "camelCase")]
+    MinLength { min_length: u32 },
+
+    /// The value is longer than the maximum length for the property.
+    #[display("must be at most {max_length} characters")]
+    #[serde(rename_all = "camelCase")]
+    MaxLength { max_length: u32 },
+
+    /// Fewer values were provided than the minimum for the property.
+    #[display("must have at least {min_items} values")]
+    #[serde(rename_all = "camelCase")]
+    MinItems { min_items: u32 },
+
+    /// More values were provided than the maximum for the property.
+    #[display("must have at most {max_items} values")]
+    #[serde(rename_all = "camelCase")]
+    MaxItems { max_items: u32 },
+
+    /// The value isn't one of the options for the property.
+    #[display("must be one of the available options")]
+    NotAnOption,
+
+    /// The value isn't valid for the type of the property.
+    #[display("must be a valid {expected}")]
+    InvalidType { expected: String },
+
+    /// The submission isn't an object, and so has no properties to validate.
+    #[display("must be an object")]
+    NotAnObject,
+}
+
+/// The set of validation failures from validating a submission against a template, keyed by the
+/// name of the property that failed.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, derive_more::Error)]
+pub struct ValidationErrors {
+    /// Failures of the submission as a whole, rather than of any single property.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub submission: Vec<Violation>,
+
+    pub properties: BTreeMap<String, Vec<Violation>>,
+}
+
+/// The reasons that validating a submission against a template can fail.
+#[derive(Debug, derive_more::Display, derive_more::Error)]
+pub enum ValidationFailure {
+    /// The submission failed validation, with the details of every failure.
+    #[display("{_0}")]
+    Invalid(ValidationErrors),
+
+    /// The template can't be used to validate submissions, such as when a property has an invalid
+    /// regex. This is a fault in the template rather than the submission.
+    #[display("{_0}")]
+    Template(Error),
+}
+
+impl ValidationErrors {
+    /// Check if there are no validation failures.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.submission.is_empty() && self.properties.is_empty()
+    }
+
+    /// Get the validation failures for a single property.
+    #[must_use]
+    pub fn get(&self, property: &str) -> &[Violation] {
+        self.properties.get(property).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl std::fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Validation failed:")?;
+
+        for violation in &self.submission {
+            write!(f, " submission {violation};")?;
+        }
+        for (property, violations) in &self.properties {
+            for violation in violations {
+                write!(f, " {property} {violation};")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Template {
+    /// Validate a JSON submission against the properties in this template.
+    ///
+    /// Properties that are not present in the template are ignored.
+    ///
+    /// # Errors
+    /// If the submission isn't a JSON object, or any of the properties in it fail validation,
+    /// with the details of every failure. If any property has an invalid regex then the template
+    /// can't be validated against at all.
+    pub fn validate(&self, submission: &Value) -> Result<(), ValidationFailure> {
+        let regexes = self
+            .properties
+            .iter()
+            .map(TemplateProperty::compile_regex)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ValidationFailure::Template)?;
+
+        let mut errors = ValidationErrors::default();
+
+        let Some(values) = submission.as_object() else {
+            errors.submission.push(Violation::NotAnObject);
+            return Err(ValidationFailure::Invalid(errors));
+        };
+
+        for (property, regex) in self.properties.iter().zip(&regexes) {
+            let violations = property.validate(values.get(&property.name), regex.as_ref());
+
+            if !violations.is_empty() {
+                errors.properties.insert(property.name.clone(), violations);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ValidationFailure::Invalid(errors))
+        }
+    }
+
+    /// Validate an `application/x-www-form-urlencoded` submission against the properties in this
+    /// template.
+    ///
+    /// Properties that are provided more than once are treated as having multiple values.
+    ///
+    /// # Errors
+    /// If any of the properties in the submission fail validation, with the details of every
+    /// failure, or any property has an invalid regex.
+    pub fn validate_form(&self, submission: &[u8]) -> Result<(), ValidationFailure> {
+        self.validate(&form_to_json(submission))
+    }
+}
+
+impl TemplateProperty {
+    /// Compile the regex for this property, if it has one.
+    ///
+    /// HAL-FORMS regexes must match the entire value, as with the HTML pattern attribute.
+    fn compile_regex(&self) -> Result<Option<Regex>, Error> {
+        self.regex
+            .as_deref()
+            .map(|regex| {
+                Regex::new(&format!("^(?:{regex})$")).map_err(|e| Error::InvalidRegex {
+                    regex:  regex.to_string(),
+                    reason: e.to_string(),
+                })
+            })
+            .transpose()
+    }
+
+    /// Validate a single value for this property, using its compiled regex, returning every
+    /// violation found.
+    fn validate(&self, value: Option<&Value>, regex: Option<&Regex>) -> Vec<Violation> {
+        let provided = match value {
+            None => vec![],
+            Some(Value::Array(values)) => values.iter().collect(),
+            Some(value) => vec![value],
+        };
+
+        // Objects and nested arrays can't be the value of a property, but they were still sent so
+        // they mustn't be treated as missing.
+        let mut values = vec![];
+        let mut non_scalar = false;
+        for value in provided {
+            match scalar_string(value) {
+                Some(value) => values.push(value),
+                None => non_scalar |= !value.is_null(),
+            }
+        }
+
+        if !non_scalar && values.iter().all(String::is_empty) {
+            return if self.required {
+                vec![Violation::Required]
+            } else {
+                vec![]
+            };
+        }
+
+        let mut violations = vec![];
+
+        if self.readonly
+            && (non_scalar
+                || values
+                    .iter()
+                    .any(|value| Some(value) != self.value.as_ref()))
+        {
+            violations.push(Violation::ReadOnly);
+        }
+
+        if non_scalar {
+            violations.push(Violation::InvalidType {
+                expected: self.r#type.clone().unwrap_or_else(|| "text".to_string()),
+            });
+        }
+
+        if let Some(options) = &self.options {
+            violations.extend(validate_options(options, &values));
+        }
+
+        // Multiple values can fail in the same way, but each failure only needs reporting once.
+        for violation in values
+            .iter()
+            .flat_map(|value| self.validate_value(value, regex))
+        {
+            if !violations.contains(&violation) {
+                violations.push(violation);
+            }
+        }
+
+        violations
+    }
+
+    /// Validate a single string value for this property.
+    fn validate_value(&self, value: &str, regex: Option<&Regex>) -> Vec<Violation> {
+        let mut violations = vec![];
+
+        if let Some(expected) = &self.r#type {
+            if !is_valid_type(expected, value) {
+                violations.push(Violation::InvalidType {
+                    expected: expected.clone(),
+                });
+            }
+        }
+
+        if let (Some(regex), Some(pattern)) = (regex, &self.regex) {
+            if !regex.is_match(value) {
+                violations.push(Violation::Regex {
+                    regex: pattern.clone(),
+                });
+            }
+        }
+
+        let length = value.chars().count();
+        if let Some(min_length) = self.min_length {
+            if length < min_length as usize {
+                violations.push(Violation::MinLength { min_length });
+            }
+        }
+        if let Some(max_length) = self.max_length {
+            if length > max_length as usize {
+                violations.push(Violation::MaxLength { max_length });
+            }
+        }
+
+        if self.min.is_some() || self.max.is_some() {
+            match value.parse::<f64>() {
+                Ok(number) => {
+                    if let Some(min) = self.min.filter(|min| number < f64::from(*min)) {
+                        violations.push(Violation::Min { min });
+                    }
+                    if let Some(max) = self.max.filter(|max| number > f64::from(*max)) {
+                        violations.push(Violation::Max { max });
+                    }
+                },
+                Err(_) => violations.push(Violation::InvalidType {
+                    expected: "number".to_string(),
+                }),
+            }
+        }
+
+        violations
+    }
+}
+
+/// Validate the set of values provided for a property against the options for that property.
+fn validate_options(options: &TemplateOptions, values: &[String]) -> Vec<Violation> {
+    let mut violations = vec![];
+
+    let (max_items, min_items) = match options {
+        TemplateOptions::Inline {
+            inline,
+            max_items,
+            min_items,
+            ..
+        } => {
+            if !values
+                .iter()
+                .all(|value| inline.iter().any(|option| &option.value == value))
+            {
+                violations.push(Violation::NotAnOption);
+            }
+
+            (max_items, min_items)
+        },
+        // The options are only available by following the link, so can't be checked here.
+        TemplateOptions::Link {
+            max_items,
+            min_items,
+            ..
+        } => (max_items, min_items),
+    };
+
+    let count = values.len();
+    if let Some(min_items) = min_items.filter(|min_items| count < *min_items as usize) {
+        violations.push(Violation::MinItems { min_items });
+    }
+    if let Some(max_items) = max_items.filter(|max_items| count > *max_items as usize) {
+        violations.push(Violation::MaxItems { max_items });
+    }
+
+    violations
+}
+
+/// Convert a single JSON value into the string that it represents, if it's a scalar value.
+fn scalar_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Check if a value is valid for the given property type.
+/// Types that aren't known about, or that can contain any text, are always valid.
+fn is_valid_type(expected: &str, value: &str) -> bool {
+    let digits = |value: &str, count: usize| {
+        value.len() == count && value.chars().all(|c| c.is_ascii_digit())
+    };
+    let date = |value: &str| {
+        let parts: Vec<&str> = value.split('-').collect();
+        parts.len() == 3 && digits(parts[0], 4) && digits(parts[1], 2) && digits(parts[2], 2)
+    };
+    let time = |value: &str| {
+        let (value, _) = value.split_once('.').unwrap_or((value, ""));
+        let parts: Vec<&str> = value.split(':').collect();
+        (parts.len() == 2 || parts.len() == 3) && parts.iter().all(|part| digits(part, 2))
+    };
+
+    match expected {
+        "number" | "range" => value.parse::<f64>().is_ok(),
+        "email" => value.split_once('@').is_some_and(|(local, domain)| {
+            !local.is_empty() && !domain.is_empty() && !value.contains(char::is_whitespace)
+        }),
+        "url" => value.split_once(':').is_some_and(|(scheme, rest)| {
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                && !rest.is_empty()
+                && !value.contains(char::is_whitespace)
+        }),
+        "tel" => value
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | ' ' | '(' | ')' | '.')),
+        "date" => date(value),
+        "time" => time(value),
+        "datetime-local" => value
+            .split_once('T')
+            .is_some_and(|(d, t)| date(d) && time(t)),
+        "month" => value
+            .split_once('-')
+            .is_some_and(|(year, month)| digits(year, 4) && digits(month, 2)),
+        "week" => value
+            .split_once("-W")
+            .is_some_and(|(year, week)| digits(year, 4) && digits(week, 2)),
+        "color" => value
+            .strip_prefix('#')
+            .is_some_and(|color| color.len() == 6 && color.chars().all(|c| c.is_ascii_hexdigit())),
+        "checkbox" => matches!(value, "true" | "false" | "on" | "off"),
+        _ => true,
+    }
+}
+
+/// Convert an `application/x-www-form-urlencoded` body into a JSON object, so that it can be
+/// validated in the same way as a JSON body.
+fn form_to_json(body: &[u8]) -> Value {
+    let mut result = Map::new();
+
+    for (name, value) in form_urlencoded::parse(body) {
+        let value = Value::String(value.into_owned());
+
+        match result.remove(name.as_ref()) {
+            None => {
+                result.insert(name.into_owned(), value);
+            },
+            Some(Value::Array(mut values)) => {
+                values.push(value);
+                result.insert(name.into_owned(), Value::Array(values));
+            },
+            Some(existing) => {
+                result.insert(name.into_owned(), Value::Array(vec![existing, value]));
+            },
+        }
+    }
+
+    Value::Object(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+    use serde_json::json;
+
+    use super::*;
+    use crate::InlineOption;
+
+    fn template() -> Template {
+        Template::default()
+            .with_property(
+                TemplateProperty::new("name")
+                    .required()
+                    .with_min_length(2u32)
+                    .with_max_length(10u32),
+            )
+            .with_property(TemplateProperty::new("code").with_regex("[A-Z]{3}"))
+            .with_property(
+                TemplateProperty::new("age")
+                    .with_type("number")
+                    .with_min(18u32)
+                    .with_max(65u32),
+            )
+            .with_property(TemplateProperty::new("id").readonly().with_value("abc"))
+            .with_property(TemplateProperty::new("email").with_type("email"))
+            .with_property(
+                TemplateProperty::new("colours").with_options(
+                    TemplateOptions::inline(vec![
+                        InlineOption::new("red"),
+                        InlineOption::new("green"),
+                        InlineOption::new("blue"),
+                    ])
+                    .with_min_items(1u32)
+                    .with_max_items(2u32),
+                ),
+            )
+    }
+
+    #[test]
+    fn valid_submission() {
+        let result = template().validate(&json!({
+            "name": "Graham",
+            "code": "ABC",
+            "age": 30,
+            "id": "abc",
+            "email": "graham@example.com",
+            "colours": ["red", "blue"],
+            "other": "ignored"
+        }));
+
+        check!(result.is_ok());
+    }
+
+    #[test]
+    fn missing_required() {
+        let result = template().validate(&json!({}));
+        let_assert!(Err(ValidationFailure::Invalid(errors)) = result);
+
+        check!(errors.properties.len() == 1);
+        check!(errors.get("name") == [Violation::Required]);
+    }
+
+    #[test]
+    fn empty_required() {
+        let result = template().validate(&json!({"name": ""}));
+        let_assert!(Err(ValidationFailure::Invalid(errors)) = result);
+
+        check!(errors.get("name") == [Violation::Required]);
+    }
+
+    #[test]
+    fn invalid_values() {
+        let result = template().validate(&json!({
+            "name": "A very long name",
+            "code": "abcd",
+            "age": 12,
+            "id": "def",
+            "email": "not an email",
+            "colours": ["red", "green", "purple"]
+        }));
+        let_assert!(Err(ValidationFailure::Invalid(errors)) = result);
+
+        check!(errors.get("name") == [Violation::MaxLength { max_length: 10 }]);
+        check!(
+            errors.get("code")
+                == [Violation::Regex {
+                    regex: "[A-Z]{3}".to_string(),
+                }]
+        );
+        check!(errors.get("age") == [Violation::Min { min: 18 }]);
+        check!(errors.get("id") == [Violation::ReadOnly]);
+        check!(
+            errors.get("email")
+                == [Violation::InvalidType {
+                    expected: "email".to_string(),
+                }]
+        );
+        check!(
+            errors.get("colours") == [Violation::NotAnOption, Violation::MaxItems { max_items: 2 }]
+        );
+    }
+
+    #[test]
+    fn non_scalar_values() {
+        let result = template().validate(&json!({
+            "name": "Graham",
+            "code": {"x": 1},
+            "age": [30, {"y": 2}],
+            "id": {"y": 2}
+        }));
+        let_assert!(Err(ValidationFailure::Invalid(errors)) = result);
+
+        check!(errors.properties.len() == 3);
+        check!(
+            errors.get("code")
+                == [Violation::InvalidType {
+                    expected: "text".to_string(),
+                }]
+        );
+        check!(
+            errors.get("age")
+                == [Violation::InvalidType {
+                    expected: "number".to_string(),
+                }]
+        );
+        check!(
+            errors.get("id")
+                == [
+                    Violation::ReadOnly,
+                    Violation::InvalidType {
+                        expected: "text".to_string(),
+                    },
+                ]
+        );
+    }
+
+    #[test]
+    fn non_scalar_required() {
+        let result = template().validate(&json!({"name": {"first": "Graham"}}));
+        let_assert!(Err(ValidationFailure::Invalid(errors)) = result);
+
+        check!(errors.properties.len() == 1);
+        check!(
+            errors.get("name")
+                == [Violation::InvalidType {
+                    expected: "text".to_string(),
+                }]
+        );
+    }
+
+    #[test]
+    fn not_an_object() {
+        for submission in [json!("hello"), json!([]), json!(null)] {
+            let result = Template::default().validate(&submission);
+            let_assert!(Err(ValidationFailure::Invalid(errors)) = result);
+
+            check!(errors.submission == [Violation::NotAnObject]);
+            check!(errors.properties.is_empty());
+            check!(errors.to_string() == "Validation failed: submission must be an object;");
+        }
+    }
+
+    #[test]
+    fn invalid_regex() {
+        let template = Template::default()
+            .with_property(TemplateProperty::new("name"))
+            .with_property(TemplateProperty::new("code").with_regex("(["));
+
+        let result = template.validate(&json!({"code": "ABC"}));
+        let_assert!(
+            Err(ValidationFailure::Template(Error::InvalidRegex {
+                regex,
+                ..
+            })) = result
+        );
+        check!(regex == "([");
+
+        let result = template.validate(&json!({}));
+        let_assert!(Err(ValidationFailure::Template(Error::InvalidRegex { .. })) = result);
+    }
+
+    #[test]
+    fn number_as_string() {
+        let result = template().validate(&json!({"name": "Graham", "age": "70"}));
+        let_assert!(Err(ValidationFailure::Invalid(errors)) = result);
+
+        check!(errors.get("age") == [Violation::Max { max: 65 }]);
+    }
+
+    #[test]
+    fn valid_form() {
+        let result = template().validate_form(b"name=Graham&age=30&colours=red&colours=green");
+
+        check!(result.is_ok());
+    }
+
+    #[test]
+    fn invalid_form() {
+        let result = template().validate_form(b"name=G&code=abc&age=old");
+        let_assert!(Err(ValidationFailure::Invalid(errors)) = result);
+
+        check!(errors.get("name") == [Violation::MinLength { min_length: 2 }]);
+        check!(errors.get("code").len() == 1);
+        check!(
+            errors.get("age")
+                == [Violation::InvalidType {
+                    expected: "number".to_string(),
+                }]
+        );
+    }
+
+    #[test]
+    fn property_types() {
+        for (expected, valid, invalid) in [
+            ("url", "https://example.com", "example"),
+            ("tel", "+44 (0)1234 567890", "call me"),
+            ("date", "2024-02-29", "29/02/2024"),
+            ("time", "12:34", "12.34"),
+            ("datetime-local", "2024-02-29T12:34:56", "2024-02-29 12:34"),
+            ("month", "2024-02", "2024-2"),
+            ("week", "2024-W09", "2024-09"),
+            ("color", "#ff00FF", "red"),
+            ("checkbox", "on", "yes"),
+        ] {
+            let template = Template::default()
+                .with_property(TemplateProperty::new("value").with_type(expected));
+
+            check!(
+                template.validate(&json!({ "value": valid })).is_ok(),
+                "{expected}"
+            );
+            check!(
+                template.validate(&json!({ "value": invalid })).is_err(),
+                "{expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn display_errors() {
+        let result = template().validate(&json!({"age": 12}));
+        let_assert!(Err(ValidationFailure::Invalid(errors)) = result);
+
+        check!(
+            errors.to_string() == "Validation failed: age must be at least 18; name is required;"
+        );
+    }
+}
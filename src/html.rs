@@ -0,0 +1,527 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    utils::single_multiple::SingleOrMultiple, Error, Hal, Link, Template, TemplateOptions,
+    TemplateProperty,
+};
+
+/// Minimal styling so that the rendered page is readable without any other assets.
+const STYLE: &str = "<style>
+body { font-family: sans-serif; margin: 2em; }
+section.embedded { border-left: 3px solid #ccc; margin: 1em 0; padding-left: 1em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }
+dt { font-weight: bold; }
+label { display: block; margin: 0.5em 0; }
+</style>
+";
+
+impl<P> Hal<P>
+where
+    P: Serialize,
+{
+    /// Render the HAL document as an HTML page, for exploring the API in a browser.
+    ///
+    /// Links become hyperlinks, embedded documents become nested sections and templates become
+    /// forms. HTML forms can only be submitted as `GET` or `POST`, so any other method or content
+    /// type of a template is recorded in the `data-method` and `data-content-type` attributes of
+    /// the form instead.
+    ///
+    /// # Errors
+    /// If the payload of the document, or of any embedded document, can't be serialized into JSON.
+    pub fn to_html(&self) -> Result<String, Error> {
+        let self_link = self.links.get("self").and_then(|links| links.iter().next());
+        let title = self_link.map_or("HAL Document", |link| {
+            link.title.as_deref().unwrap_or(&link.href)
+        });
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<title>");
+        escape(&mut html, title);
+        html.push_str("</title>\n");
+        html.push_str(STYLE);
+        html.push_str("</head>\n<body>\n<main>\n");
+        render_document(&mut html, self)?;
+        html.push_str("</main>\n</body>\n</html>\n");
+
+        Ok(html)
+    }
+}
+
+/// Render the contents of a single document, including any embedded documents.
+fn render_document<P>(html: &mut String, hal: &Hal<P>) -> Result<(), Error>
+where
+    P: Serialize,
+{
+    if !hal.links.is_empty() {
+        render_links(html, &hal.links);
+    }
+
+    let payload = serde_json::to_value(&hal.payload).map_err(Error::Serialization)?;
+    let empty = match &payload {
+        Value::Null => true,
+        Value::Object(values) => values.is_empty(),
+        _ => false,
+    };
+    if !empty {
+        html.push_str("<h2>Properties</h2>\n");
+        render_value(html, &payload);
+        html.push('\n');
+    }
+
+    let self_href = hal
+        .links
+        .get("self")
+        .and_then(|links| links.iter().next())
+        .map(|link| link.href.as_str());
+    for (name, template) in &hal.templates {
+        render_template(html, name, template, self_href);
+    }
+
+    for (rel, documents) in &hal.embedded {
+        for document in documents.iter() {
+            html.push_str("<section class=\"embedded\">\n<h2>");
+            escape(html, rel);
+            html.push_str("</h2>\n");
+            render_document(html, document)?;
+            html.push_str("</section>\n");
+        }
+    }
+
+    Ok(())
+}
+
+/// Render the links of a document as a table.
+fn render_links(html: &mut String, links: &BTreeMap<String, SingleOrMultiple<Link>>) {
+    html.push_str("<h2>Links</h2>\n<table class=\"links\">\n");
+    html.push_str("<tr><th>Relation</th><th>Link</th><th>Title</th></tr>\n");
+
+    for (rel, links) in links {
+        for link in links.iter() {
+            html.push_str("<tr><td>");
+            escape(html, rel);
+            html.push_str("</td><td>");
+
+            if !link.templated && is_navigable(&link.href) {
+                html.push_str("<a");
+                attribute(html, "href", Some(&link.href));
+                attribute(html, "type", link.r#type.as_ref());
+                attribute(html, "hreflang", link.hreflang.as_ref());
+                html.push('>');
+                escape(html, &link.href);
+                html.push_str("</a>");
+            } else {
+                html.push_str("<code>");
+                escape(html, &link.href);
+                html.push_str("</code>");
+            }
+
+            html.push_str("</td><td>");
+            escape(html, link.title.as_deref().unwrap_or_default());
+            html.push_str("</td></tr>\n");
+        }
+    }
+
+    html.push_str("</table>\n");
+}
+
+/// Render a JSON value, with objects as definition lists and arrays as ordered lists.
+fn render_value(html: &mut String, value: &Value) {
+    match value {
+        Value::Object(values) => {
+            html.push_str("<dl>");
+            for (key, value) in values {
+                html.push_str("<dt>");
+                escape(html, key);
+                html.push_str("</dt><dd>");
+                render_value(html, value);
+                html.push_str("</dd>");
+            }
+            html.push_str("</dl>");
+        },
+        Value::Array(values) => {
+            html.push_str("<ol>");
+            for value in values {
+                html.push_str("<li>");
+                render_value(html, value);
+                html.push_str("</li>");
+            }
+            html.push_str("</ol>");
+        },
+        Value::String(value) => escape(html, value),
+        other => escape(html, &other.to_string()),
+    }
+}
+
+/// Render a template as a form.
+fn render_template(html: &mut String, name: &str, template: &Template, self_href: Option<&str>) {
+    let method = template.method.as_deref().unwrap_or("POST");
+    let content_type = template
+        .content_type
+        .as_deref()
+        .unwrap_or("application/json");
+    let title = template.title.as_deref().unwrap_or(name);
+
+    html.push_str("<form class=\"template\"");
+    attribute(html, "name", Some(name));
+    attribute(
+        html,
+        "method",
+        Some(if method.eq_ignore_ascii_case("GET") {
+            "get"
+        } else {
+            "post"
+        }),
+    );
+    attribute(
+        html,
+        "action",
+        template
+            .target
+            .as_deref()
+            .or(self_href)
+            .filter(|target| is_navigable(target)),
+    );
+    if matches!(
+        content_type,
+        "application/x-www-form-urlencoded" | "multipart/form-data" | "text/plain"
+    ) {
+        attribute(html, "enctype", Some(content_type));
+    }
+    attribute(html, "data-method", Some(method));
+    attribute(html, "data-content-type", Some(content_type));
+    html.push_str(">\n<fieldset>\n<legend>");
+    escape(html, title);
+    html.push_str("</legend>\n");
+
+    for property in &template.properties {
+        render_property(html, property);
+    }
+
+    html.push_str("<button type=\"submit\">");
+    escape(html, title);
+    html.push_str("</button>\n</fieldset>\n</form>\n");
+}
+
+/// Render a single template property as a labelled form control.
+fn render_property(html: &mut String, property: &TemplateProperty) {
+    let r#type = property.r#type.as_deref().unwrap_or("text");
+
+    html.push_str("<label>");
+    escape(html, property.prompt.as_deref().unwrap_or(&property.name));
+    html.push('\n');
+
+    if let Some(TemplateOptions::Inline {
+        inline,
+        max_items,
+        selected_values,
+        ..
+    }) = &property.options
+    {
+        let multiple = *max_items != Some(1);
+
+        html.push_str("<select");
+        attribute(html, "name", Some(&property.name));
+        flag(html, "multiple", multiple);
+        flag(html, "required", property.required);
+        flag(html, "disabled", property.readonly);
+        html.push_str(">\n");
+
+        if !multiple && !property.required {
+            html.push_str("<option value=\"\"></option>\n");
+        }
+        for option in inline {
+            let selected = selected_values.contains(&option.value)
+                || property.value.as_ref() == Some(&option.value);
+
+            html.push_str("<option");
+            attribute(html, "value", Some(&option.value));
+            flag(html, "selected", selected);
+            html.push('>');
+            escape(html, option.prompt.as_deref().unwrap_or(&option.value));
+            html.push_str("</option>\n");
+        }
+
+        html.push_str("</select>\n");
+    } else if r#type == "textarea" {
+        html.push_str("<textarea");
+        attribute(html, "name", Some(&property.name));
+        attribute(html, "cols", property.cols);
+        attribute(html, "rows", property.rows);
+        attribute(html, "placeholder", property.placeholder.as_ref());
+        attribute(html, "minlength", property.min_length);
+        attribute(html, "maxlength", property.max_length);
+        flag(html, "required", property.required);
+        flag(html, "readonly", property.readonly);
+        html.push('>');
+        escape(html, property.value.as_deref().unwrap_or_default());
+        html.push_str("</textarea>\n");
+    } else {
+        html.push_str("<input");
+        attribute(html, "type", Some(r#type));
+        attribute(html, "name", Some(&property.name));
+        attribute(html, "value", property.value.as_ref());
+        attribute(html, "placeholder", property.placeholder.as_ref());
+        attribute(html, "pattern", property.regex.as_ref());
+        attribute(html, "min", property.min);
+        attribute(html, "max", property.max);
+        attribute(html, "minlength", property.min_length);
+        attribute(html, "maxlength", property.max_length);
+        attribute(html, "step", property.step);
+        flag(html, "required", property.required);
+        flag(html, "readonly", property.readonly);
+        html.push_str(">\n");
+    }
+
+    html.push_str("</label>\n");
+}
+
+/// Write an attribute with an escaped value, if there is a value.
+fn attribute<V>(html: &mut String, name: &str, value: Option<V>)
+where
+    V: Display,
+{
+    if let Some(value) = value {
+        html.push(' ');
+        html.push_str(name);
+        html.push_str("=\"");
+        escape(html, &value.to_string());
+        html.push('"');
+    }
+}
+
+/// Write a boolean attribute, if it is set.
+fn flag(html: &mut String, name: &str, value: bool) {
+    if value {
+        html.push(' ');
+        html.push_str(name);
+    }
+}
+
+/// Write a value escaped for use in either HTML text or a quoted attribute.
+fn escape(html: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+}
+
+/// Determine if an href is safe to use as a hyperlink.
+///
+/// Escaping doesn't stop links such as `javascript:` from running code, so only relative
+/// references and HTTP URLs are allowed.
+fn is_navigable(href: &str) -> bool {
+    match href.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => {
+            scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https")
+        },
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::check;
+    use insta::assert_snapshot;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        values::{HttpMethod, TemplateContentType, TemplatePropertyType},
+        InlineOption,
+    };
+
+    #[test]
+    fn escaping() {
+        let mut html = String::new();
+        escape(&mut html, r#"<a href="x">Tom & 'Jerry'</a>"#);
+
+        check!(html == "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;");
+    }
+
+    #[test]
+    fn navigable() {
+        check!(is_navigable("/users/1"));
+        check!(is_navigable("users?a=b:c"));
+        check!(is_navigable("https://example.com/"));
+        check!(is_navigable("HTTP://example.com/"));
+        check!(!is_navigable("javascript:alert(1)"));
+        check!(!is_navigable(" javascript:alert(1)"));
+        check!(!is_navigable("data:text/html,hi"));
+    }
+
+    #[test]
+    fn full_page() {
+        let hal = Hal::new(json!({"name": "Graham", "tags": ["a", "b"], "age": 42}))
+            .with_link("self", Link::new("/users/1").with_title("Graham"))
+            .with_link("search", Link::new("/users{?q}").templated())
+            .with_embedded(
+                "address",
+                Hal::new(json!({"city": "Leeds"})).with_link("self", "/addresses/1"),
+            )
+            .with_template(
+                "search",
+                Template::default()
+                    .with_method(HttpMethod::GET)
+                    .with_content_type(TemplateContentType::Form)
+                    .with_target("/users")
+                    .with_property(TemplateProperty::new("q").with_prompt("Query")),
+            );
+
+        assert_snapshot!(hal.to_html().unwrap(), @r###"
+        <!DOCTYPE html>
+        <html>
+        <head>
+        <meta charset="utf-8">
+        <title>Graham</title>
+        <style>
+        body { font-family: sans-serif; margin: 2em; }
+        section.embedded { border-left: 3px solid #ccc; margin: 1em 0; padding-left: 1em; }
+        table { border-collapse: collapse; }
+        th, td { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }
+        dt { font-weight: bold; }
+        label { display: block; margin: 0.5em 0; }
+        </style>
+        </head>
+        <body>
+        <main>
+        <h2>Links</h2>
+        <table class="links">
+        <tr><th>Relation</th><th>Link</th><th>Title</th></tr>
+        <tr><td>search</td><td><code>/users{?q}</code></td><td></td></tr>
+        <tr><td>self</td><td><a href="/users/1">/users/1</a></td><td>Graham</td></tr>
+        </table>
+        <h2>Properties</h2>
+        <dl><dt>name</dt><dd>Graham</dd><dt>tags</dt><dd><ol><li>a</li><li>b</li></ol></dd><dt>age</dt><dd>42</dd></dl>
+        <form class="template" name="search" method="get" action="/users" enctype="application/x-www-form-urlencoded" data-method="GET" data-content-type="application/x-www-form-urlencoded">
+        <fieldset>
+        <legend>search</legend>
+        <label>Query
+        <input type="text" name="q">
+        </label>
+        <button type="submit">search</button>
+        </fieldset>
+        </form>
+        <section class="embedded">
+        <h2>address</h2>
+        <h2>Links</h2>
+        <table class="links">
+        <tr><th>Relation</th><th>Link</th><th>Title</th></tr>
+        <tr><td>self</td><td><a href="/addresses/1">/addresses/1</a></td><td></td></tr>
+        </table>
+        <h2>Properties</h2>
+        <dl><dt>city</dt><dd>Leeds</dd></dl>
+        </section>
+        </main>
+        </body>
+        </html>
+        "###);
+    }
+
+    #[test]
+    fn form_controls() {
+        let template = Template::default()
+            .with_method(HttpMethod::PUT)
+            .with_title("Update")
+            .with_property(
+                TemplateProperty::new("name")
+                    .required()
+                    .with_value("Graham")
+                    .with_regex("[A-Z].*")
+                    .with_max_length(20u32),
+            )
+            .with_property(
+                TemplateProperty::new("age")
+                    .with_type(TemplatePropertyType::Number)
+                    .with_min(18u32)
+                    .with_max(150u32),
+            )
+            .with_property(
+                TemplateProperty::new("bio")
+                    .with_type("textarea")
+                    .with_rows(5u32)
+                    .with_value("<Hello>"),
+            )
+            .with_property(
+                TemplateProperty::new("role").with_options(
+                    TemplateOptions::inline(vec![
+                        InlineOption::new("admin").with_prompt("Administrator"),
+                        InlineOption::new("user"),
+                    ])
+                    .with_max_items(1u32)
+                    .with_selected_value("user"),
+                ),
+            )
+            .with_property(
+                TemplateProperty::new("tags")
+                    .required()
+                    .with_options(TemplateOptions::inline(vec!["a", "b"])),
+            );
+
+        let mut html = String::new();
+        render_template(&mut html, "default", &template, Some("/users/1"));
+
+        assert_snapshot!(html, @r###"
+        <form class="template" name="default" method="post" action="/users/1" data-method="PUT" data-content-type="application/json">
+        <fieldset>
+        <legend>Update</legend>
+        <label>name
+        <input type="text" name="name" value="Graham" pattern="[A-Z].*" maxlength="20" required>
+        </label>
+        <label>age
+        <input type="number" name="age" min="18" max="150">
+        </label>
+        <label>bio
+        <textarea name="bio" rows="5">&lt;Hello&gt;</textarea>
+        </label>
+        <label>role
+        <select name="role">
+        <option value=""></option>
+        <option value="admin">Administrator</option>
+        <option value="user" selected>user</option>
+        </select>
+        </label>
+        <label>tags
+        <select name="tags" multiple required>
+        <option value="a">a</option>
+        <option value="b">b</option>
+        </select>
+        </label>
+        <button type="submit">Update</button>
+        </fieldset>
+        </form>
+        "###);
+    }
+
+    #[test]
+    fn escapes_values() {
+        let hal = Hal::new(json!({"<b>": "<script>alert('hi')</script>"}))
+            .with_link("x\"y", Link::new("javascript:alert(1)").with_title("<i>"))
+            .with_template(
+                "t",
+                Template::default()
+                    .with_target("javascript:alert(1)")
+                    .with_property(TemplateProperty::new("a\"b").with_value("\"><script>")),
+            );
+
+        let html = hal.to_html().unwrap();
+
+        check!(!html.contains("<script>"));
+        check!(!html.contains("<b>"));
+        check!(!html.contains("<i>"));
+        check!(!html.contains("href=\"javascript"));
+        check!(!html.contains("action=\"javascript"));
+        check!(html.contains("<code>javascript:alert(1)</code>"));
+        check!(html.contains("name=\"a&quot;b\" value=\"&quot;&gt;&lt;script&gt;\""));
+    }
+}
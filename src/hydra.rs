@@ -0,0 +1,440 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    hal::curie::{Curies, CURIES_REL},
+    utils::single_multiple::SingleOrMultiple,
+    Error, Hal, Link, Template, UriTemplate,
+};
+
+/// The IRI of the Hydra core vocabulary.
+const HYDRA: &str = "http://www.w3.org/ns/hydra/core#";
+
+/// Configuration of the `@context` used when rendering HAL documents as JSON-LD with Hydra.
+///
+/// The context always includes the `hydra` prefix, plus any CURIEs registered on the documents
+/// whose href ends with the `{rel}` placeholder. Prefixes given here take priority over CURIEs
+/// with the same name.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HydraContext {
+    /// The vocabulary used for payload fields and link relations that aren't IRIs.
+    pub vocab: Option<String>,
+
+    /// Prefixes for compact IRIs, keyed by name.
+    pub prefixes: BTreeMap<String, String>,
+}
+
+impl HydraContext {
+    /// Set the vocabulary used for payload fields and link relations that aren't IRIs.
+    #[must_use]
+    pub fn with_vocab<S>(mut self, value: S) -> Self
+    where
+        S: ToString,
+    {
+        self.vocab = Some(value.to_string());
+
+        self
+    }
+
+    /// Add a prefix for compact IRIs.
+    #[must_use]
+    pub fn with_prefix<N, S>(mut self, name: N, iri: S) -> Self
+    where
+        N: ToString,
+        S: ToString,
+    {
+        self.prefixes.insert(name.to_string(), iri.to_string());
+
+        self
+    }
+}
+
+impl<P> Hal<P>
+where
+    P: Serialize,
+{
+    /// Render the HAL document as JSON-LD using the Hydra vocabulary.
+    ///
+    /// The self link becomes the `@id` of the node and the payload becomes its properties. Other
+    /// links become references to other nodes, templated links become `hydra:IriTemplate`s with a
+    /// mapping for each variable, and embedded documents become nested nodes. Templates become
+    /// `hydra:Operation`s on the node, with `hydra:expects` describing the properties. Hydra
+    /// attaches operations to the resource itself, so the target of a template isn't represented.
+    ///
+    /// # Errors
+    /// If the payload of the document, or of any embedded document, can't be serialized into a
+    /// JSON object, if any payload field has the same name as a link relation or embedded document
+    /// of that document, or if any templated link isn't a valid URI Template.
+    pub fn to_hydra(&self, context: &HydraContext) -> Result<Value, Error> {
+        let mut result = Map::new();
+
+        let mut prefixes = Map::new();
+        prefixes.insert("hydra".to_string(), Value::String(HYDRA.to_string()));
+        if let Some(vocab) = &context.vocab {
+            prefixes.insert("@vocab".to_string(), Value::String(vocab.clone()));
+        }
+        for (name, iri) in &context.prefixes {
+            prefixes.insert(name.clone(), Value::String(iri.clone()));
+        }
+        curie_prefixes(self, &Curies::default(), &mut prefixes);
+        result.insert("@context".to_string(), Value::Object(prefixes));
+
+        result.extend(node(self)?);
+
+        Ok(Value::Object(result))
+    }
+}
+
+/// Add the CURIEs of a document, and any embedded into it, to the prefixes of the context.
+fn curie_prefixes<P>(hal: &Hal<P>, inherited: &Curies, prefixes: &mut Map<String, Value>) {
+    let curies = inherited.extend_from(&hal.links);
+
+    for (name, iri) in curies.prefixes() {
+        prefixes
+            .entry(name)
+            .or_insert_with(|| Value::String(iri.to_string()));
+    }
+
+    for embedded in hal.embedded.values().flat_map(SingleOrMultiple::iter) {
+        curie_prefixes(embedded, &curies, prefixes);
+    }
+}
+
+/// Render a single document as a JSON-LD node.
+fn node<P>(hal: &Hal<P>) -> Result<Map<String, Value>, Error>
+where
+    P: Serialize,
+{
+    let mut result = Map::new();
+
+    if let Some(link) = hal.links.get("self").and_then(|links| links.iter().next()) {
+        result.insert("@id".to_string(), Value::String(link.href.clone()));
+    }
+
+    match serde_json::to_value(&hal.payload).map_err(Error::Serialization)? {
+        Value::Null => {},
+        Value::Object(payload) => {
+            for (name, value) in payload {
+                insert_unique(&mut result, &name, value)?;
+            }
+        },
+        _ => return Err(Error::NotAnObject),
+    }
+
+    for (rel, links) in &hal.links {
+        if rel == CURIES_REL {
+            continue;
+        }
+
+        // The first self link is already the `@id` of the node.
+        let values = links
+            .iter()
+            .skip(usize::from(rel == "self"))
+            .map(link)
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(value) = single_or_array(links, values) {
+            insert_unique(&mut result, rel, value)?;
+        }
+    }
+
+    for (rel, documents) in &hal.embedded {
+        let values = documents
+            .iter()
+            .map(|document| node(document).map(Value::Object))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(value) = single_or_array(documents, values) {
+            insert_unique(&mut result, rel, value)?;
+        }
+    }
+
+    if !hal.templates.is_empty() {
+        let operations = hal
+            .templates
+            .iter()
+            .map(|(name, template)| operation(name, template))
+            .collect();
+        insert_unique(&mut result, "hydra:operation", Value::Array(operations))?;
+    }
+
+    Ok(result)
+}
+
+/// Insert a property into a node, failing if it already has a property with that name - such as
+/// a payload field with the same name as a link relation - rather than silently replacing it.
+fn insert_unique(node: &mut Map<String, Value>, name: &str, value: Value) -> Result<(), Error> {
+    if node.contains_key(name) {
+        return Err(Error::ConflictingProperty(name.to_string()));
+    }
+    node.insert(name.to_string(), value);
+
+    Ok(())
+}
+
+/// Render a single link, either as a reference to another node or as an IRI template.
+fn link(link: &Link) -> Result<Value, Error> {
+    let mut result = Map::new();
+
+    if link.templated {
+        let template = UriTemplate::parse(&link.href)?;
+        let mappings = template
+            .variables()
+            .into_iter()
+            .map(|variable| {
+                Value::Object(object([
+                    ("@type", Value::from("hydra:IriTemplateMapping")),
+                    ("hydra:variable", Value::from(variable)),
+                    ("hydra:property", Value::from(variable)),
+                    ("hydra:required", Value::Bool(false)),
+                ]))
+            })
+            .collect();
+
+        result.insert("@type".to_string(), Value::from("hydra:IriTemplate"));
+        result.insert(
+            "hydra:template".to_string(),
+            Value::from(link.href.as_str()),
+        );
+        result.insert("hydra:mapping".to_string(), Value::Array(mappings));
+    } else {
+        result.insert("@id".to_string(), Value::from(link.href.as_str()));
+    }
+
+    if let Some(title) = &link.title {
+        result.insert("hydra:title".to_string(), Value::from(title.as_str()));
+    }
+
+    Ok(Value::Object(result))
+}
+
+/// Render a single template as a Hydra operation.
+fn operation(name: &str, template: &Template) -> Value {
+    let properties = template
+        .properties
+        .iter()
+        .map(|property| {
+            let mut result = object([
+                ("@type", Value::from("hydra:SupportedProperty")),
+                ("hydra:property", Value::from(property.name.as_str())),
+                ("hydra:required", Value::Bool(property.required)),
+                ("hydra:writeable", Value::Bool(!property.readonly)),
+            ]);
+            if let Some(prompt) = &property.prompt {
+                result.insert("hydra:title".to_string(), Value::from(prompt.as_str()));
+            }
+
+            Value::Object(result)
+        })
+        .collect();
+
+    let mut result = object([
+        ("@type", Value::from("hydra:Operation")),
+        (
+            "hydra:title",
+            Value::from(template.title.as_deref().unwrap_or(name)),
+        ),
+        (
+            "hydra:method",
+            Value::from(template.method.as_deref().unwrap_or("POST")),
+        ),
+    ]);
+    if !template.properties.is_empty() {
+        let expects = object([
+            ("@type", Value::from("hydra:Class")),
+            ("hydra:supportedProperty", Value::Array(properties)),
+        ]);
+        result.insert("hydra:expects".to_string(), Value::Object(expects));
+    }
+
+    Value::Object(result)
+}
+
+/// Build a JSON object from the given entries.
+fn object<const N: usize>(entries: [(&str, Value); N]) -> Map<String, Value> {
+    entries
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+}
+
+/// Produce a single value or an array of values, matching the shape of the original values, or
+/// `None` if there are no values left.
+fn single_or_array<T>(original: &SingleOrMultiple<T>, mut values: Vec<Value>) -> Option<Value>
+where
+    T: Serialize,
+{
+    match original {
+        SingleOrMultiple::Single(_) => values.pop(),
+        SingleOrMultiple::Multiple(_) if values.is_empty() => None,
+        SingleOrMultiple::Multiple(_) => Some(Value::Array(values)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+    use insta::assert_json_snapshot;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{values::HttpMethod, TemplateProperty};
+
+    #[test]
+    fn context() {
+        let hal = Hal::new(())
+            .with_curie("acme", "https://example.com/rels/{rel}")
+            .with_curie("docs", "https://example.com/docs/{rel}.html")
+            .with_embedded(
+                "acme:child",
+                Hal::new(()).with_curie("other", "https://example.com/other/{rel}"),
+            );
+        let context = HydraContext::default()
+            .with_vocab("https://example.com/vocab#")
+            .with_prefix("schema", "https://schema.org/")
+            .with_prefix("acme", "https://example.com/override/");
+
+        let_assert!(Ok(hydra) = hal.to_hydra(&context));
+
+        assert_json_snapshot!(hydra["@context"], @r###"
+        {
+          "hydra": "http://www.w3.org/ns/hydra/core#",
+          "@vocab": "https://example.com/vocab#",
+          "acme": "https://example.com/override/",
+          "schema": "https://schema.org/",
+          "other": "https://example.com/other/"
+        }
+        "###);
+    }
+
+    #[test]
+    fn full_document() {
+        let hal = Hal::new(json!({"name": "Graham"}))
+            .with_link("self", "/users/1")
+            .with_link(
+                "acme:friends",
+                Link::new("/users/1/friends").with_title("Friends"),
+            )
+            .with_link("search", Link::new("/users{?q,page}").templated())
+            .with_embedded(
+                "acme:address",
+                Hal::new(json!({"city": "Leeds"})).with_link("self", "/addresses/1"),
+            )
+            .with_template(
+                "default",
+                Template::default()
+                    .with_method(HttpMethod::PUT)
+                    .with_title("Update")
+                    .with_property(TemplateProperty::new("name").required().with_prompt("Name"))
+                    .with_property(TemplateProperty::new("id").readonly()),
+            )
+            .with_template(
+                "delete",
+                Template::default().with_method(HttpMethod::DELETE),
+            );
+
+        let_assert!(Ok(hydra) = hal.to_hydra(&HydraContext::default()));
+
+        assert_json_snapshot!(hydra, @r###"
+        {
+          "@context": {
+            "hydra": "http://www.w3.org/ns/hydra/core#"
+          },
+          "@id": "/users/1",
+          "name": "Graham",
+          "acme:friends": {
+            "@id": "/users/1/friends",
+            "hydra:title": "Friends"
+          },
+          "search": {
+            "@type": "hydra:IriTemplate",
+            "hydra:template": "/users{?q,page}",
+            "hydra:mapping": [
+              {
+                "@type": "hydra:IriTemplateMapping",
+                "hydra:variable": "q",
+                "hydra:property": "q",
+                "hydra:required": false
+              },
+              {
+                "@type": "hydra:IriTemplateMapping",
+                "hydra:variable": "page",
+                "hydra:property": "page",
+                "hydra:required": false
+              }
+            ]
+          },
+          "acme:address": {
+            "@id": "/addresses/1",
+            "city": "Leeds"
+          },
+          "hydra:operation": [
+            {
+              "@type": "hydra:Operation",
+              "hydra:title": "Update",
+              "hydra:method": "PUT",
+              "hydra:expects": {
+                "@type": "hydra:Class",
+                "hydra:supportedProperty": [
+                  {
+                    "@type": "hydra:SupportedProperty",
+                    "hydra:property": "name",
+                    "hydra:required": true,
+                    "hydra:writeable": true,
+                    "hydra:title": "Name"
+                  },
+                  {
+                    "@type": "hydra:SupportedProperty",
+                    "hydra:property": "id",
+                    "hydra:required": false,
+                    "hydra:writeable": false
+                  }
+                ]
+              }
+            },
+            {
+              "@type": "hydra:Operation",
+              "hydra:title": "delete",
+              "hydra:method": "DELETE"
+            }
+          ]
+        }
+        "###);
+    }
+
+    #[test]
+    fn invalid_templated_link() {
+        let hal = Hal::new(()).with_link("search", Link::new("/users{?q").templated());
+
+        let_assert!(Err(Error::InvalidUriTemplate { .. }) = hal.to_hydra(&HydraContext::default()));
+    }
+
+    #[test]
+    fn conflicting_properties() {
+        let context = HydraContext::default();
+
+        let hal = Hal::new(json!({"author": "Graham"})).with_link("author", "/users/1");
+        let_assert!(Err(Error::ConflictingProperty(name)) = hal.to_hydra(&context));
+        check!(name == "author");
+
+        let hal = Hal::new(json!({"comments": []})).with_embedded(
+            "comments",
+            Hal::new(json!({"text": "Hello"})).with_link("self", "/comments/1"),
+        );
+        let_assert!(Err(Error::ConflictingProperty(name)) = hal.to_hydra(&context));
+        check!(name == "comments");
+
+        let hal = Hal::new(json!({"@id": "/other"})).with_link("self", "/users/1");
+        let_assert!(Err(Error::ConflictingProperty(name)) = hal.to_hydra(&context));
+        check!(name == "@id");
+
+        let hal = Hal::new(()).with_embedded(
+            "author",
+            Hal::new(json!({"self": "me"}))
+                .with_link("self", "/users/1")
+                .with_link("self", "/u/1"),
+        );
+        let_assert!(Err(Error::ConflictingProperty(name)) = hal.to_hydra(&context));
+        check!(name == "self");
+    }
+}
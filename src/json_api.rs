@@ -0,0 +1,628 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{utils::single_multiple::SingleOrMultiple, AnyPayload, Error, Hal, Link, Template};
+
+/// How the resources in a HAL document are identified when rendering it as JSON:API.
+///
+/// JSON:API needs a `type` and `id` for every resource, which HAL documents don't have. The id is
+/// taken from a field of the payload, and the type is given for the top-level resources and for
+/// each link relation that other resources are embedded under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonApiMapping {
+    /// The type of the top-level resource, or of the members if the document is a collection.
+    pub r#type: String,
+
+    /// The link relation that the members are embedded under, if the document is a collection.
+    pub collection: Option<String>,
+
+    /// The field of the payload that holds the id of each resource.
+    pub id_field: String,
+
+    /// The types of embedded resources, keyed by the link relation that they are embedded under.
+    pub types: BTreeMap<String, String>,
+}
+
+impl JsonApiMapping {
+    /// Create a mapping for a document that is a single resource of the given type.
+    #[must_use]
+    pub fn resource<S>(r#type: S) -> Self
+    where
+        S: ToString,
+    {
+        Self {
+            r#type:     r#type.to_string(),
+            collection: None,
+            id_field:   "id".to_string(),
+            types:      BTreeMap::new(),
+        }
+    }
+
+    /// Create a mapping for a document that is a collection of resources of the given type,
+    /// embedded under the given link relation.
+    #[must_use]
+    pub fn collection<R, S>(rel: R, r#type: S) -> Self
+    where
+        R: ToString,
+        S: ToString,
+    {
+        Self {
+            collection: Some(rel.to_string()),
+            ..Self::resource(r#type)
+        }
+    }
+
+    /// Set the field of the payload that holds the id of each resource.
+    #[must_use]
+    pub fn with_id_field<S>(mut self, value: S) -> Self
+    where
+        S: ToString,
+    {
+        self.id_field = value.to_string();
+
+        self
+    }
+
+    /// Set the type of the resources embedded under the given link relation.
+    #[must_use]
+    pub fn with_type<R, S>(mut self, rel: R, r#type: S) -> Self
+    where
+        R: ToString,
+        S: ToString,
+    {
+        self.types.insert(rel.to_string(), r#type.to_string());
+
+        self
+    }
+}
+
+/// A HAL document rendered as JSON:API, along with the parts that couldn't be represented.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonApi {
+    /// The JSON:API document.
+    pub document: Value,
+
+    /// The parts of the HAL document that were left out of the JSON:API document.
+    pub unrepresentable: Vec<JsonApiUnrepresentable>,
+}
+
+/// A part of a HAL document that can't be represented in JSON:API.
+///
+/// The `path` is the link relations leading to the embedded document that the part is in,
+/// separated by `/`, or empty for the top-level document.
+#[derive(Debug, Clone, PartialEq, Eq, derive_more::Display)]
+pub enum JsonApiUnrepresentable {
+    /// JSON:API has no equivalent to HAL-FORMS templates.
+    #[display("Template {name} at '{path}'")]
+    Template { path: String, name: String },
+
+    /// JSON:API links must be URIs, so can't be templated.
+    #[display("Templated link {rel} at '{path}'")]
+    TemplatedLink { path: String, rel: String },
+
+    /// JSON:API only allows a single link for each relation, so all but the first are left out.
+    #[display("Additional link {rel} at '{path}'")]
+    AdditionalLink { path: String, rel: String },
+
+    /// The document has no id, so can't be a relationship or included resource.
+    #[display("Missing id at '{path}'")]
+    MissingId { path: String },
+
+    /// The mapping has no type for the documents embedded at this path.
+    #[display("Unmapped type at '{path}'")]
+    UnmappedType { path: String },
+
+    /// A collection can only have relationships to its members, so any other embedded documents
+    /// are left out.
+    #[display("Embedded {rel} in collection")]
+    CollectionEmbedded { rel: String },
+}
+
+impl<P> Hal<P>
+where
+    P: Serialize,
+{
+    /// Render the HAL document as a JSON:API document, using the given mapping to identify the
+    /// resources.
+    ///
+    /// The payload becomes the `attributes` and the links become `links`. Embedded documents
+    /// become `relationships`, with the documents themselves de-duplicated into `included`. If the
+    /// mapping is for a collection then the members become the primary `data`, and the payload
+    /// and links of the collection become the top-level `meta` and `links`.
+    ///
+    /// # Errors
+    /// If the payload of the document, or of any embedded document, can't be serialized into a
+    /// JSON object.
+    pub fn to_json_api(&self, mapping: &JsonApiMapping) -> Result<JsonApi, Error> {
+        let mut renderer = Renderer {
+            mapping,
+            included: vec![],
+            identities: BTreeSet::new(),
+            unrepresentable: vec![],
+        };
+        let mut document = Map::new();
+        let mut primary = BTreeSet::new();
+
+        if let Some(rel) = &mapping.collection {
+            let mut data = vec![];
+            for member in self
+                .embedded
+                .get(rel)
+                .iter()
+                .flat_map(|members| members.iter())
+            {
+                let resource = renderer.resource(member, &mapping.r#type, rel)?;
+                primary.extend(resource.identity);
+                data.push(Value::Object(resource.object));
+            }
+            document.insert("data".to_string(), Value::Array(data));
+
+            let meta = payload_object(&self.payload)?;
+            if !meta.is_empty() {
+                document.insert("meta".to_string(), Value::Object(meta));
+            }
+
+            let links = renderer.links(&self.links, "");
+            if !links.is_empty() {
+                document.insert("links".to_string(), Value::Object(links));
+            }
+
+            renderer.templates(&self.templates, "");
+            for other in self.embedded.keys().filter(|other| *other != rel) {
+                renderer
+                    .unrepresentable
+                    .push(JsonApiUnrepresentable::CollectionEmbedded { rel: other.clone() });
+            }
+        } else {
+            let resource = renderer.resource(self, &mapping.r#type, "")?;
+            primary.extend(resource.identity);
+            document.insert("data".to_string(), Value::Object(resource.object));
+        }
+
+        // The primary data must not be repeated in the included resources.
+        let included: Vec<Value> = renderer
+            .included
+            .into_iter()
+            .filter(|(identity, _)| !primary.contains(identity))
+            .map(|(_, object)| Value::Object(object))
+            .collect();
+        if !included.is_empty() {
+            document.insert("included".to_string(), Value::Array(included));
+        }
+
+        Ok(JsonApi {
+            document:        Value::Object(document),
+            unrepresentable: renderer.unrepresentable,
+        })
+    }
+}
+
+/// The type and id of a resource.
+type Identity = (String, String);
+
+/// A single rendered resource object.
+struct Resource {
+    identity: Option<Identity>,
+    object:   Map<String, Value>,
+}
+
+/// State for rendering a JSON:API document.
+struct Renderer<'a> {
+    mapping:         &'a JsonApiMapping,
+    included:        Vec<(Identity, Map<String, Value>)>,
+    identities:      BTreeSet<Identity>,
+    unrepresentable: Vec<JsonApiUnrepresentable>,
+}
+
+impl Renderer<'_> {
+    /// Render a single document as a resource object, including any of the documents embedded
+    /// into it.
+    fn resource<P>(&mut self, hal: &Hal<P>, r#type: &str, path: &str) -> Result<Resource, Error>
+    where
+        P: Serialize,
+    {
+        let mut attributes = payload_object(&hal.payload)?;
+        let id = match attributes.remove(&self.mapping.id_field) {
+            Some(Value::String(id)) => Some(id),
+            Some(Value::Number(id)) => Some(id.to_string()),
+            _ => None,
+        };
+
+        let mut object = Map::new();
+        object.insert("type".to_string(), Value::String(r#type.to_string()));
+        match &id {
+            Some(id) => {
+                object.insert("id".to_string(), Value::String(id.clone()));
+            },
+            None => self
+                .unrepresentable
+                .push(JsonApiUnrepresentable::MissingId {
+                    path: path.to_string(),
+                }),
+        }
+        if !attributes.is_empty() {
+            object.insert("attributes".to_string(), Value::Object(attributes));
+        }
+
+        let relationships = self.relationships(&hal.embedded, path)?;
+        if !relationships.is_empty() {
+            object.insert("relationships".to_string(), Value::Object(relationships));
+        }
+
+        let links = self.links(&hal.links, path);
+        if !links.is_empty() {
+            object.insert("links".to_string(), Value::Object(links));
+        }
+
+        self.templates(&hal.templates, path);
+
+        Ok(Resource {
+            identity: id.map(|id| (r#type.to_string(), id)),
+            object,
+        })
+    }
+
+    /// Render the embedded documents as relationships, adding the documents themselves to the
+    /// included resources.
+    fn relationships(
+        &mut self,
+        embedded: &BTreeMap<String, SingleOrMultiple<Hal<AnyPayload>>>,
+        path: &str,
+    ) -> Result<Map<String, Value>, Error> {
+        let mut relationships = Map::new();
+
+        for (rel, documents) in embedded {
+            let path = if path.is_empty() {
+                rel.clone()
+            } else {
+                format!("{path}/{rel}")
+            };
+            let Some(r#type) = self.mapping.types.get(rel) else {
+                self.unrepresentable
+                    .push(JsonApiUnrepresentable::UnmappedType { path });
+                continue;
+            };
+
+            let mut linkage = vec![];
+            for document in documents.iter() {
+                let resource = self.resource(document, r#type, &path)?;
+                if let Some(identity) = resource.identity {
+                    linkage.push(identifier(&identity));
+                    if self.identities.insert(identity.clone()) {
+                        self.included.push((identity, resource.object));
+                    }
+                }
+            }
+
+            let data = match documents {
+                SingleOrMultiple::Single(_) => match linkage.pop() {
+                    Some(single) => single,
+                    None => continue,
+                },
+                SingleOrMultiple::Multiple(_) => Value::Array(linkage),
+            };
+
+            let mut relationship = Map::new();
+            relationship.insert("data".to_string(), data);
+            relationships.insert(rel.clone(), Value::Object(relationship));
+        }
+
+        Ok(relationships)
+    }
+
+    /// Render the links of a document, recording any that can't be represented.
+    fn links(
+        &mut self,
+        links: &BTreeMap<String, SingleOrMultiple<Link>>,
+        path: &str,
+    ) -> Map<String, Value> {
+        let mut result = Map::new();
+
+        for (rel, values) in links {
+            for link in values.iter() {
+                if link.templated {
+                    self.unrepresentable
+                        .push(JsonApiUnrepresentable::TemplatedLink {
+                            path: path.to_string(),
+                            rel:  rel.clone(),
+                        });
+                } else if result.contains_key(rel) {
+                    self.unrepresentable
+                        .push(JsonApiUnrepresentable::AdditionalLink {
+                            path: path.to_string(),
+                            rel:  rel.clone(),
+                        });
+                } else {
+                    result.insert(rel.clone(), link_value(link));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Record the templates of a document as unrepresentable.
+    fn templates(&mut self, templates: &BTreeMap<String, Template>, path: &str) {
+        for name in templates.keys() {
+            self.unrepresentable.push(JsonApiUnrepresentable::Template {
+                path: path.to_string(),
+                name: name.clone(),
+            });
+        }
+    }
+}
+
+/// Serialize a payload into a JSON object, treating `null` as an empty object.
+fn payload_object<P>(payload: &P) -> Result<Map<String, Value>, Error>
+where
+    P: Serialize,
+{
+    match serde_json::to_value(payload).map_err(Error::Serialization)? {
+        Value::Null => Ok(Map::new()),
+        Value::Object(object) => Ok(object),
+        _ => Err(Error::NotAnObject),
+    }
+}
+
+/// Build the resource identifier object for a resource.
+fn identifier((r#type, id): &Identity) -> Value {
+    let mut object = Map::new();
+    object.insert("type".to_string(), Value::String(r#type.clone()));
+    object.insert("id".to_string(), Value::String(id.clone()));
+
+    Value::Object(object)
+}
+
+/// Render a single link, as a plain URI unless it has details that need a link object.
+fn link_value(link: &Link) -> Value {
+    if link.title.is_none() && link.r#type.is_none() && link.hreflang.is_none() {
+        return Value::String(link.href.clone());
+    }
+
+    let mut object = Map::new();
+    object.insert("href".to_string(), Value::String(link.href.clone()));
+    for (key, value) in [
+        ("title", &link.title),
+        ("type", &link.r#type),
+        ("hreflang", &link.hreflang),
+    ] {
+        if let Some(value) = value {
+            object.insert(key.to_string(), Value::String(value.clone()));
+        }
+    }
+
+    Value::Object(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+    use insta::assert_json_snapshot;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn resource() {
+        let hal = Hal::new(json!({"id": 1, "title": "Hello"}))
+            .with_link("self", "/articles/1")
+            .with_link(
+                "describedby",
+                Link::new("/schema").with_type("application/schema+json"),
+            )
+            .with_embedded(
+                "author",
+                Hal::new(json!({"id": "9", "name": "Graham"})).with_link("self", "/people/9"),
+            )
+            .with_embedded("comments", Hal::new(json!({"id": 5, "body": "First"})))
+            .with_embedded("comments", Hal::new(json!({"id": 6, "body": "Second"})));
+
+        let mapping = JsonApiMapping::resource("articles")
+            .with_type("author", "people")
+            .with_type("comments", "comments");
+
+        let_assert!(Ok(json_api) = hal.to_json_api(&mapping));
+
+        check!(json_api.unrepresentable == vec![]);
+        assert_json_snapshot!(json_api.document, @r###"
+        {
+          "data": {
+            "type": "articles",
+            "id": "1",
+            "attributes": {
+              "title": "Hello"
+            },
+            "relationships": {
+              "author": {
+                "data": {
+                  "type": "people",
+                  "id": "9"
+                }
+              },
+              "comments": {
+                "data": [
+                  {
+                    "type": "comments",
+                    "id": "5"
+                  },
+                  {
+                    "type": "comments",
+                    "id": "6"
+                  }
+                ]
+              }
+            },
+            "links": {
+              "describedby": {
+                "href": "/schema",
+                "type": "application/schema+json"
+              },
+              "self": "/articles/1"
+            }
+          },
+          "included": [
+            {
+              "type": "people",
+              "id": "9",
+              "attributes": {
+                "name": "Graham"
+              },
+              "links": {
+                "self": "/people/9"
+              }
+            },
+            {
+              "type": "comments",
+              "id": "5",
+              "attributes": {
+                "body": "First"
+              }
+            },
+            {
+              "type": "comments",
+              "id": "6",
+              "attributes": {
+                "body": "Second"
+              }
+            }
+          ]
+        }
+        "###);
+    }
+
+    #[test]
+    fn collection() {
+        let author = || Hal::new(json!({"id": "9", "name": "Graham"}));
+        let hal = Hal::new(json!({"total": 2}))
+            .with_link("self", "/articles")
+            .with_embedded(
+                "items",
+                Hal::new(json!({"id": 1, "title": "One"})).with_embedded("author", author()),
+            )
+            .with_embedded(
+                "items",
+                Hal::new(json!({"id": 2, "title": "Two"})).with_embedded("author", author()),
+            );
+
+        let mapping = JsonApiMapping::collection("items", "articles").with_type("author", "people");
+
+        let_assert!(Ok(json_api) = hal.to_json_api(&mapping));
+
+        check!(json_api.unrepresentable == vec![]);
+        assert_json_snapshot!(json_api.document, @r###"
+        {
+          "data": [
+            {
+              "type": "articles",
+              "id": "1",
+              "attributes": {
+                "title": "One"
+              },
+              "relationships": {
+                "author": {
+                  "data": {
+                    "type": "people",
+                    "id": "9"
+                  }
+                }
+              }
+            },
+            {
+              "type": "articles",
+              "id": "2",
+              "attributes": {
+                "title": "Two"
+              },
+              "relationships": {
+                "author": {
+                  "data": {
+                    "type": "people",
+                    "id": "9"
+                  }
+                }
+              }
+            }
+          ],
+          "meta": {
+            "total": 2
+          },
+          "links": {
+            "self": "/articles"
+          },
+          "included": [
+            {
+              "type": "people",
+              "id": "9",
+              "attributes": {
+                "name": "Graham"
+              }
+            }
+          ]
+        }
+        "###);
+    }
+
+    #[test]
+    fn unrepresentable() {
+        let hal = Hal::new(json!({"title": "Hello"}))
+            .with_link("search", Link::new("/articles{?q}").templated())
+            .with_link("item", "/articles/1")
+            .with_link("item", "/articles/2")
+            .with_embedded("tags", Hal::new(json!({"id": "a"})))
+            .with_embedded("author", Hal::new(json!({"name": "Graham"})))
+            .with_template("default", Template::default());
+
+        let mapping = JsonApiMapping::resource("articles").with_type("author", "people");
+
+        let_assert!(Ok(json_api) = hal.to_json_api(&mapping));
+
+        check!(
+            json_api.unrepresentable
+                == vec![
+                    JsonApiUnrepresentable::MissingId {
+                        path: String::new(),
+                    },
+                    JsonApiUnrepresentable::MissingId {
+                        path: "author".to_string(),
+                    },
+                    JsonApiUnrepresentable::UnmappedType {
+                        path: "tags".to_string(),
+                    },
+                    JsonApiUnrepresentable::AdditionalLink {
+                        path: String::new(),
+                        rel:  "item".to_string(),
+                    },
+                    JsonApiUnrepresentable::TemplatedLink {
+                        path: String::new(),
+                        rel:  "search".to_string(),
+                    },
+                    JsonApiUnrepresentable::Template {
+                        path: String::new(),
+                        name: "default".to_string(),
+                    },
+                ]
+        );
+        assert_json_snapshot!(json_api.document, @r###"
+        {
+          "data": {
+            "type": "articles",
+            "attributes": {
+              "title": "Hello"
+            },
+            "links": {
+              "item": "/articles/1"
+            }
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn payload_not_an_object() {
+        let_assert!(
+            Err(Error::NotAnObject) = Hal::new(42).to_json_api(&JsonApiMapping::resource("x"))
+        );
+    }
+}
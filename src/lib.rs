@@ -1,9 +1,69 @@
+//! Support for HAL and HAL-FORMS responses in HTTP APIs.
+//!
+//! Documents are built with [`Hal`] and returned from handlers as a [`HalResponse`].
+//!
+//! # Content negotiation
+//!
+//! By default the `Accept` header of the request is ignored, and a response is always returned in
+//! the richest representation that the document needs, even if the client doesn't accept it.
+//! Negotiation has to be opted into:
+//!
+//! - With Axum, add the `negotiate` middleware to the router. Handlers that it doesn't wrap never
+//!   negotiate, and so never return `406 Not Acceptable`.
+//! - With Salvo, add the `Negotiate` handler to the router as a hoop.
+//! - With any server, pass the accepted media ranges to [`HalResponse::with_accept`] in the
+//!   handler. This takes precedence over the middleware.
+
+#[cfg(feature = "actix")]
+mod actix;
 #[cfg(feature = "axum")]
 mod axum;
+mod collection_json;
+mod cursor;
+mod error;
 mod hal;
+mod html;
+mod hydra;
+mod json_api;
+mod link_header;
+mod negotiation;
+mod page;
+#[cfg(feature = "poem")]
+mod poem;
+mod resource;
 mod response;
+#[cfg(feature = "salvo")]
+mod salvo;
+mod siren;
 mod utils;
 pub mod values;
+#[cfg(feature = "xml")]
+mod xml;
 
+pub use cursor::*;
+pub use error::*;
 pub use hal::*;
+#[cfg(feature = "derive")]
+pub use http_halforms_derive::{HalResource, HalTemplate};
+pub use hydra::*;
+pub use json_api::*;
+pub use link_header::*;
+pub use negotiation::*;
+pub use page::*;
+pub use resource::*;
 pub use response::*;
+
+#[cfg(feature = "axum")]
+pub use crate::axum::*;
+#[cfg(feature = "salvo")]
+pub use crate::salvo::*;
+
+// Allows the derive macros to refer to `::http_halforms` from within this crate.
+extern crate self as http_halforms;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+
+    pub use crate::utils::enum_options::enum_options;
+}
@@ -0,0 +1,1025 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use http::HeaderName;
+use serde_json::{Map, Value};
+
+use crate::{
+    hal::curie::{Curies, CURIES_REL},
+    Error, Hal, Link,
+};
+
+/// The name of the RFC 9652 `Link-Template` header.
+pub const LINK_TEMPLATE: HeaderName = HeaderName::from_static("link-template");
+
+/// The extension on a link that holds the base URI for the variables of its template.
+const VAR_BASE: &str = "var-base";
+
+/// Which links of a document are mirrored into the `Link` header of a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkHeaders {
+    /// Every link in the document.
+    All,
+    /// Only the links with one of the given relations.
+    Only(BTreeSet<String>),
+}
+
+impl LinkHeaders {
+    /// Whether links with the given relation are mirrored.
+    #[must_use]
+    pub fn includes(&self, rel: &str) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(rels) => rels.contains(rel),
+        }
+    }
+}
+
+/// Build the value of an RFC 8288 `Link` header for the selected links of a document, or `None`
+/// if there are no such links.
+///
+/// The `title`, `type`, `hreflang` and `name` of each link become link-params. Templated links
+/// can't be represented in a `Link` header, so they are left out. CURIEs are expanded, since the
+/// relations in a `Link` header must be either registered names or full URIs.
+pub(crate) fn link_header<P>(hal: &Hal<P>, selection: &LinkHeaders) -> Option<String> {
+    let values = selected_links(hal, selection)
+        .filter(|(_, link)| !link.templated)
+        .map(|(rel, link)| link_value(&rel, link))
+        .collect::<Vec<_>>();
+
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+/// Build the value of an RFC 9652 `Link-Template` header for the selected templated links of a
+/// document, or `None` if there are no such links.
+///
+/// The `title`, `type`, `hreflang` and `name` of each link become parameters, as does any
+/// `var-base` extension. CURIEs are expanded, as for the `Link` header.
+pub(crate) fn link_template_header<P>(hal: &Hal<P>, selection: &LinkHeaders) -> Option<String> {
+    let values = selected_links(hal, selection)
+        .filter(|(_, link)| link.templated)
+        .map(|(rel, link)| link_template_value(&rel, link))
+        .collect::<Vec<_>>();
+
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+/// Iterate over the selected links of a document, along with their relations with any CURIEs
+/// expanded.
+fn selected_links<'a, P>(
+    hal: &'a Hal<P>,
+    selection: &'a LinkHeaders,
+) -> impl Iterator<Item = (String, &'a Link)> {
+    let curies = Curies::default().extend_from(&hal.links);
+
+    hal.links
+        .iter()
+        .filter(|(rel, _)| *rel != CURIES_REL && selection.includes(rel))
+        .flat_map(move |(rel, links)| {
+            let rel = curies.expand(rel).unwrap_or_else(|| rel.clone());
+            links.iter().map(move |link| (rel.clone(), link))
+        })
+}
+
+/// Parse the value of an RFC 8288 `Link` header into links, along with their relations.
+///
+/// A link with several relations is returned once for each of them, and can be merged into a
+/// document with [`Hal::with_links`]. The `title`, `type`, `hreflang` and `name` parameters fill
+/// in the matching fields of the link, and any others, such as `anchor`, become string extensions,
+/// or `true` if they have no value. RFC 8187 extended values such as `title*` are decoded and take
+/// precedence over their plain versions, and otherwise only the first occurrence of a parameter is
+/// used. If a response has several `Link` headers then their values should be joined with commas
+/// first.
+///
+/// # Errors
+/// If the value isn't a valid `Link` header, or any link has no `rel` parameter.
+pub fn parse_link_header(value: &str) -> Result<Vec<(String, Link)>, Error> {
+    let mut parser = HeaderParser::new(value);
+    let mut result = vec![];
+
+    for (href, params) in parser.link_values()? {
+        result.extend(with_relations(Link::new(href), params)?);
+    }
+
+    Ok(result)
+}
+
+/// Parse the value of an RFC 9652 `Link-Template` header into templated links, along with their
+/// relations.
+///
+/// A link with several relations is returned once for each of them. The `title`, `type`,
+/// `hreflang` and `name` parameters fill in the matching fields of the link, and any others, such
+/// as `anchor` and `var-base`, become extensions. If a response has several `Link-Template`
+/// headers then their values should be joined with commas first.
+///
+/// # Errors
+/// If the value isn't a valid Structured Field List of strings, any member has no `rel` parameter,
+/// or any member isn't a valid URI Template.
+pub fn parse_link_template_header(value: &str) -> Result<Vec<(String, Link)>, Error> {
+    let mut parser = HeaderParser::new(value);
+    let mut result = vec![];
+
+    for (template, params) in parser.list()? {
+        let Value::String(template) = template else {
+            return Err(Error::InvalidLinkHeader(
+                "Link-Template members must be strings".to_string(),
+            ));
+        };
+
+        result.extend(with_relations(
+            Link::new(template).try_templated()?,
+            params,
+        )?);
+    }
+
+    Ok(result)
+}
+
+/// Fill in a parsed link from its parameters, and pair it with each of its relations.
+///
+/// Registered relation names are compared case-insensitively, so they are lowercased, while
+/// extension relations are URIs and are left as they are.
+fn with_relations(
+    mut link: Link,
+    params: Map<String, Value>,
+) -> Result<Vec<(String, Link)>, Error> {
+    let mut rels = None;
+    for (name, value) in params {
+        match (name.as_str(), value) {
+            ("rel", Value::String(value)) => rels = Some(value),
+            ("title", Value::String(value)) => link.title = Some(value),
+            ("type", Value::String(value)) => link.r#type = Some(value),
+            ("hreflang", Value::String(value)) => link.hreflang = Some(value),
+            ("name", Value::String(value)) => link.name = Some(value),
+            (_, value) => {
+                link.extensions.insert(name, value);
+            },
+        }
+    }
+
+    let rels =
+        rels.ok_or_else(|| Error::InvalidLinkHeader(format!("no relation for {}", link.href)))?;
+
+    Ok(rels
+        .split_ascii_whitespace()
+        .map(|rel| {
+            let rel = if rel.contains(':') {
+                rel.to_string()
+            } else {
+                rel.to_ascii_lowercase()
+            };
+
+            (rel, link.clone())
+        })
+        .collect())
+}
+
+/// Format a single templated link as a member of a `Link-Template` header.
+fn link_template_value(rel: &str, link: &Link) -> String {
+    let mut result = String::from("\"");
+    for c in link.href.chars() {
+        match c {
+            '"' | '\\' => {
+                result.push('\\');
+                result.push(c);
+            },
+            ' '..='~' => result.push(c),
+            c => {
+                let mut buffer = [0; 4];
+                for byte in c.encode_utf8(&mut buffer).bytes() {
+                    let _ = write!(result, "%{byte:02X}");
+                }
+            },
+        }
+    }
+    result.push('"');
+
+    let var_base = link.extensions.get(VAR_BASE).and_then(Value::as_str);
+    for (name, value) in [
+        ("rel", Some(rel)),
+        ("title", link.title.as_deref()),
+        ("type", link.r#type.as_deref()),
+        ("hreflang", link.hreflang.as_deref()),
+        ("name", link.name.as_deref()),
+        (VAR_BASE, var_base),
+    ] {
+        if let Some(value) = value {
+            result.push(';');
+            result.push_str(name);
+            result.push('=');
+            structured_string(&mut result, value);
+        }
+    }
+
+    result
+}
+
+/// Write a value as a Structured Field string, or as a Display String if it isn't printable
+/// ASCII.
+fn structured_string(result: &mut String, value: &str) {
+    if value.chars().all(|c| matches!(c, ' '..='~')) {
+        result.push('"');
+        for c in value.chars() {
+            if matches!(c, '"' | '\\') {
+                result.push('\\');
+            }
+            result.push(c);
+        }
+        result.push('"');
+    } else {
+        result.push_str("%\"");
+        for byte in value.bytes() {
+            if matches!(byte, b' '..=b'~') && !matches!(byte, b'%' | b'"') {
+                result.push(char::from(byte));
+            } else {
+                let _ = write!(result, "%{byte:02x}");
+            }
+        }
+        result.push('"');
+    }
+}
+
+/// Format a single link as a link-value.
+fn link_value(rel: &str, link: &Link) -> String {
+    let mut result = String::from("<");
+    encode_uri(&mut result, &link.href);
+    result.push('>');
+
+    param(&mut result, "rel", Some(rel));
+    param(&mut result, "title", link.title.as_deref());
+    param(&mut result, "type", link.r#type.as_deref());
+    param(&mut result, "hreflang", link.hreflang.as_deref());
+    param(&mut result, "name", link.name.as_deref());
+
+    result
+}
+
+/// Write a link-param, if it has a value.
+///
+/// Values that are printable ASCII are written as a quoted-string. Anything else is written as an
+/// RFC 8187 ext-value, with the name suffixed by `*`, since header values can only be ASCII.
+fn param(result: &mut String, name: &str, value: Option<&str>) {
+    let Some(value) = value else {
+        return;
+    };
+
+    result.push_str("; ");
+    result.push_str(name);
+
+    if value.chars().all(|c| matches!(c, ' '..='~')) {
+        result.push_str("=\"");
+        for c in value.chars() {
+            if matches!(c, '"' | '\\') {
+                result.push('\\');
+            }
+            result.push(c);
+        }
+        result.push('"');
+    } else {
+        result.push_str("*=UTF-8''");
+        for byte in value.bytes() {
+            if byte.is_ascii_alphanumeric() || b"!#$&+-.^_`|~".contains(&byte) {
+                result.push(char::from(byte));
+            } else {
+                let _ = write!(result, "%{byte:02X}");
+            }
+        }
+    }
+}
+
+/// Write a URI reference so that it can be used between the angle brackets of a link-value.
+///
+/// The href should already be a valid URI reference, but anything that isn't printable ASCII, or
+/// would end the URI reference early, is percent-encoded to be safe.
+fn encode_uri(result: &mut String, href: &str) {
+    for c in href.chars() {
+        if matches!(c, '!'..='~') && !matches!(c, '<' | '>' | '"') {
+            result.push(c);
+        } else {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                let _ = write!(result, "%{byte:02X}");
+            }
+        }
+    }
+}
+
+/// Decode an RFC 8187 extended value, such as `UTF-8'en'%E2%82%AC%20rates`, or `None` if it isn't
+/// valid.
+fn decode_ext_value(value: &str) -> Option<String> {
+    let mut parts = value.splitn(3, '\'');
+    let (charset, _language, encoded) = (parts.next()?, parts.next()?, parts.next()?);
+
+    let mut bytes = vec![];
+    let mut rest = encoded.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+            let hex = std::str::from_utf8(hex).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+
+    if charset.eq_ignore_ascii_case("utf-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("iso-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
+/// A parser for the values of the RFC 8288 `Link` header, and for the subset of RFC 9651
+/// Structured Field Values used by headers such as `Link-Template`.
+///
+/// Bare items are parsed into JSON values, with strings, display strings and tokens all becoming
+/// strings. Byte sequences, dates and inner lists aren't supported.
+struct HeaderParser<'a> {
+    input: &'a str,
+}
+
+/// A single item of a Structured Field List, along with its parameters.
+type Item = (Value, Map<String, Value>);
+
+/// A single link-value of a `Link` header: its URI reference, along with its parameters.
+type LinkValue = (String, Map<String, Value>);
+
+impl<'a> HeaderParser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input: input.trim_matches(' '),
+        }
+    }
+
+    /// Parse the entire input as a List.
+    fn list(&mut self) -> Result<Vec<Item>, Error> {
+        let mut result = vec![];
+
+        while !self.input.is_empty() {
+            let item = self.bare_item()?;
+            let params = self.parameters()?;
+            result.push((item, params));
+
+            self.skip_ows();
+            if self.input.is_empty() {
+                break;
+            }
+            self.expect(',')?;
+            self.skip_ows();
+            if self.input.is_empty() {
+                return Err(self.error("trailing comma"));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Parse the parameters of an item.
+    fn parameters(&mut self) -> Result<Map<String, Value>, Error> {
+        let mut result = Map::new();
+
+        while self.input.starts_with(';') {
+            self.advance(1);
+            self.input = self.input.trim_start_matches(' ');
+
+            let key = self.key()?;
+            let value = if self.input.starts_with('=') {
+                self.advance(1);
+                self.bare_item()?
+            } else {
+                Value::Bool(true)
+            };
+            result.insert(key, value);
+        }
+
+        Ok(result)
+    }
+
+    /// Parse the key of a parameter.
+    fn key(&mut self) -> Result<String, Error> {
+        if !self
+            .input
+            .starts_with(|c: char| c.is_ascii_lowercase() || c == '*')
+        {
+            return Err(self.error("expected a key"));
+        }
+
+        Ok(self
+            .take_while(|c| {
+                c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.' | '*')
+            })
+            .to_string())
+    }
+
+    /// Parse a single bare item.
+    fn bare_item(&mut self) -> Result<Value, Error> {
+        match self.input.chars().next() {
+            Some('"') => self.string(),
+            Some('%') => self.display_string(),
+            Some('?') => {
+                let value = match self.input.get(..2) {
+                    Some("?1") => true,
+                    Some("?0") => false,
+                    _ => return Err(self.error("invalid boolean")),
+                };
+                self.advance(2);
+
+                Ok(Value::Bool(value))
+            },
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) if c == '*' || c.is_ascii_alphabetic() => {
+                let token = self
+                    .take_while(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~:/".contains(c));
+
+                Ok(Value::String(token.to_string()))
+            },
+            _ => Err(self.error("unsupported item")),
+        }
+    }
+
+    /// Parse a string, including the surrounding quotes.
+    fn string(&mut self) -> Result<Value, Error> {
+        self.expect('"')?;
+
+        let mut result = String::new();
+        let mut chars = self.input.char_indices();
+        loop {
+            match chars.next() {
+                Some((index, '"')) => {
+                    self.advance(index + 1);
+                    return Ok(Value::String(result));
+                },
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, c @ ('"' | '\\'))) => result.push(c),
+                    _ => return Err(self.error("invalid escape in string")),
+                },
+                Some((_, c @ ' '..='~')) => result.push(c),
+                Some(_) => return Err(self.error("invalid character in string")),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    /// Parse a Display String, including the leading `%` and the surrounding quotes.
+    fn display_string(&mut self) -> Result<Value, Error> {
+        self.expect('%')?;
+        self.expect('"')?;
+
+        let mut result = vec![];
+        let mut bytes = self.input.bytes().enumerate();
+        loop {
+            match bytes.next() {
+                Some((index, b'"')) => {
+                    self.advance(index + 1);
+                    return String::from_utf8(result)
+                        .map(Value::String)
+                        .map_err(|_| self.error("invalid UTF-8 in display string"));
+                },
+                Some((index, b'%')) => {
+                    let byte = self
+                        .input
+                        .get(index + 1..index + 3)
+                        .filter(|hex| hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')))
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                        .ok_or_else(|| self.error("invalid escape in display string"))?;
+                    result.push(byte);
+                    bytes.nth(1);
+                },
+                Some((_, byte @ b' '..=b'~')) => result.push(byte),
+                Some(_) => return Err(self.error("invalid character in display string")),
+                None => return Err(self.error("unterminated display string")),
+            }
+        }
+    }
+
+    /// Parse an integer or decimal.
+    fn number(&mut self) -> Result<Value, Error> {
+        let number = self.take_while(|c| c == '-' || c == '.' || c.is_ascii_digit());
+
+        number
+            .parse::<i64>()
+            .map(Value::from)
+            .ok()
+            .or_else(|| number.parse::<f64>().ok().map(Value::from))
+            .ok_or_else(|| self.error("invalid number"))
+    }
+
+    /// Parse the entire input as the value of a `Link` header.
+    fn link_values(&mut self) -> Result<Vec<LinkValue>, Error> {
+        let mut result = vec![];
+
+        loop {
+            // Empty list elements are allowed, so any stray commas are skipped.
+            self.input = self.input.trim_start_matches([' ', '\t', ',']);
+            if self.input.is_empty() {
+                return Ok(result);
+            }
+
+            self.expect('<')?;
+            let href = self.take_while(|c| c != '>').to_string();
+            self.expect('>')?;
+            result.push((href, self.link_params()?));
+
+            self.skip_ows();
+            if !self.input.is_empty() {
+                self.expect(',')?;
+            }
+        }
+    }
+
+    /// Parse the parameters of a link-value.
+    fn link_params(&mut self) -> Result<Map<String, Value>, Error> {
+        let mut result = Map::new();
+        let mut extended = BTreeSet::new();
+
+        loop {
+            self.skip_ows();
+            if !self.input.starts_with(';') {
+                return Ok(result);
+            }
+            self.advance(1);
+            self.skip_ows();
+
+            let name = self.token()?.to_ascii_lowercase();
+            self.skip_ows();
+            let value = if self.input.starts_with('=') {
+                self.advance(1);
+                self.skip_ows();
+                if self.input.starts_with('"') {
+                    Some(self.quoted_string()?)
+                } else {
+                    Some(self.token()?.to_string())
+                }
+            } else {
+                None
+            };
+
+            if let Some(name) = name.strip_suffix('*') {
+                let value = value
+                    .as_deref()
+                    .and_then(decode_ext_value)
+                    .ok_or_else(|| self.error(&format!("invalid value for {name}*")))?;
+                if extended.insert(name.to_string()) {
+                    result.insert(name.to_string(), Value::String(value));
+                }
+            } else if !result.contains_key(&name) {
+                result.insert(name, value.map_or(Value::Bool(true), Value::String));
+            }
+        }
+    }
+
+    /// Parse a token, which can't be empty.
+    fn token(&mut self) -> Result<&'a str, Error> {
+        let token = self.take_while(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c));
+        if token.is_empty() {
+            return Err(self.error("expected a token"));
+        }
+
+        Ok(token)
+    }
+
+    /// Parse an RFC 9110 quoted-string, including the surrounding quotes.
+    fn quoted_string(&mut self) -> Result<String, Error> {
+        self.expect('"')?;
+
+        let mut result = String::new();
+        let mut chars = self.input.char_indices();
+        loop {
+            match chars.next() {
+                Some((index, '"')) => {
+                    self.advance(index + 1);
+                    return Ok(result);
+                },
+                Some((_, '\\')) => match chars.next() {
+                    Some((_, c)) if c == '\t' || !c.is_control() => result.push(c),
+                    _ => return Err(self.error("invalid escape in quoted string")),
+                },
+                Some((_, c)) if c == '\t' || !c.is_control() => result.push(c),
+                Some(_) => return Err(self.error("invalid character in quoted string")),
+                None => return Err(self.error("unterminated quoted string")),
+            }
+        }
+    }
+
+    /// Skip any optional whitespace.
+    fn skip_ows(&mut self) {
+        self.input = self.input.trim_start_matches([' ', '\t']);
+    }
+
+    /// Consume the given character, which must be next.
+    fn expect(&mut self, expected: char) -> Result<(), Error> {
+        if !self.input.starts_with(expected) {
+            return Err(self.error(&format!("expected '{expected}'")));
+        }
+        self.advance(expected.len_utf8());
+
+        Ok(())
+    }
+
+    /// Consume characters while they match the predicate, returning them.
+    fn take_while<F>(&mut self, predicate: F) -> &'a str
+    where
+        F: Fn(char) -> bool,
+    {
+        let end = self
+            .input
+            .find(|c| !predicate(c))
+            .unwrap_or(self.input.len());
+        let (result, rest) = self.input.split_at(end);
+        self.input = rest;
+
+        result
+    }
+
+    /// Consume the given number of bytes.
+    fn advance(&mut self, length: usize) {
+        self.input = &self.input[length..];
+    }
+
+    /// Build an error for the current position in the input.
+    fn error(&self, reason: &str) -> Error {
+        Error::InvalidLinkHeader(format!("{reason} at \"{}\"", self.input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+
+    use super::*;
+
+    #[test]
+    fn all_links() {
+        let hal = Hal::new(())
+            .with_link("self", "/users?page=2")
+            .with_link("next", Link::new("/users?page=3").with_title("Next page"))
+            .with_link("item", "/users/1")
+            .with_link("item", "/users/2")
+            .with_link("search", Link::new("/users{?q}").templated());
+
+        check!(
+            link_header(&hal, &LinkHeaders::All).as_deref()
+                == Some(
+                    "</users/1>; rel=\"item\", </users/2>; rel=\"item\", \
+                     </users?page=3>; rel=\"next\"; title=\"Next page\", \
+                     </users?page=2>; rel=\"self\""
+                )
+        );
+    }
+
+    #[test]
+    fn selected_links() {
+        let hal = Hal::new(())
+            .with_link("self", "/users?page=2")
+            .with_link("next", "/users?page=3")
+            .with_link("prev", "/users?page=1");
+        let selection = LinkHeaders::Only(["next".to_string(), "prev".to_string()].into());
+
+        check!(
+            link_header(&hal, &selection).as_deref()
+                == Some("</users?page=3>; rel=\"next\", </users?page=1>; rel=\"prev\"")
+        );
+
+        let selection = LinkHeaders::Only(["other".to_string()].into());
+        check!(link_header(&hal, &selection) == None);
+    }
+
+    #[test]
+    fn link_params() {
+        let hal = Hal::new(()).with_link(
+            "alternate",
+            Link::new("/users/1.xml")
+                .with_title("Say \"hi\" \\ bye")
+                .with_type("application/hal+xml")
+                .with_hreflang("en-GB")
+                .with_name("xml"),
+        );
+
+        check!(
+            link_header(&hal, &LinkHeaders::All).as_deref()
+                == Some(
+                    "</users/1.xml>; rel=\"alternate\"; title=\"Say \\\"hi\\\" \\\\ bye\"; \
+                     type=\"application/hal+xml\"; hreflang=\"en-GB\"; name=\"xml\""
+                )
+        );
+    }
+
+    #[test]
+    fn non_ascii_values() {
+        let hal = Hal::new(()).with_link(
+            "author",
+            Link::new("/people/Zoë Smith>").with_title("Zoë\nSmith"),
+        );
+
+        check!(
+            link_header(&hal, &LinkHeaders::All).as_deref()
+                == Some(
+                    "</people/Zo%C3%AB%20Smith%3E>; rel=\"author\"; \
+                     title*=UTF-8''Zo%C3%AB%0ASmith"
+                )
+        );
+    }
+
+    #[test]
+    fn curies() {
+        let hal = Hal::new(())
+            .with_curie("acme", "https://example.com/rels/{rel}")
+            .with_link("acme:widgets", "/widgets");
+
+        check!(
+            link_header(&hal, &LinkHeaders::All).as_deref()
+                == Some("</widgets>; rel=\"https://example.com/rels/widgets\"")
+        );
+    }
+
+    #[test]
+    fn link_templates() {
+        let mut search = Link::new("/users{?q}").templated().with_title("Search");
+        search.extensions.insert(
+            "var-base".to_string(),
+            Value::from("https://example.com/vars/"),
+        );
+        let hal = Hal::new(())
+            .with_link("self", "/users")
+            .with_link("search", search)
+            .with_link(
+                "user",
+                Link::new("/users/{id}").templated().with_title("Zoë"),
+            );
+
+        check!(
+            link_template_header(&hal, &LinkHeaders::All).as_deref()
+                == Some(
+                    "\"/users{?q}\";rel=\"search\";title=\"Search\";\
+                     var-base=\"https://example.com/vars/\", \
+                     \"/users/{id}\";rel=\"user\";title=%\"Zo%c3%ab\""
+                )
+        );
+
+        let selection = LinkHeaders::Only(["self".to_string()].into());
+        check!(link_template_header(&hal, &selection) == None);
+    }
+
+    #[test]
+    fn parse_link_templates() {
+        let_assert!(
+            Ok(links) = parse_link_template_header(
+                "\"/{username}\"; rel=\"item https://example.org/rel/user\", \
+                 \"/books/{isbn}\";rel=\"item\";title=%\"Zo%c3%ab %22books%22\";anchor=\"#x\";\
+                 var-base=\"https://example.org/vars/\";type=\"text/html\";hreflang=en;\
+                 name=\"books\";deprecated;weight=1.5"
+            )
+        );
+
+        let rels = links
+            .iter()
+            .map(|(rel, _)| rel.as_str())
+            .collect::<Vec<_>>();
+        check!(rels == ["item", "https://example.org/rel/user", "item"]);
+        check!(links[0].1 == links[1].1);
+        check!(links[0].1 == Link::new("/{username}").templated());
+
+        let books = &links[2].1;
+        check!(books.href == "/books/{isbn}");
+        check!(books.templated);
+        check!(books.title.as_deref() == Some("Zoë \"books\""));
+        check!(books.r#type.as_deref() == Some("text/html"));
+        check!(books.hreflang.as_deref() == Some("en"));
+        check!(books.name.as_deref() == Some("books"));
+        check!(books.extensions["anchor"] == "#x");
+        check!(books.extensions["var-base"] == "https://example.org/vars/");
+        check!(books.extensions["deprecated"] == true);
+        check!(books.extensions["weight"] == 1.5);
+    }
+
+    #[test]
+    fn link_templates_round_trip() {
+        let mut search = Link::new("/search{?q}")
+            .templated()
+            .with_title("Ünïcödé \"search\"");
+        search
+            .extensions
+            .insert("var-base".to_string(), Value::from("https://example.com/"));
+        let hal = Hal::new(()).with_link("search", search.clone());
+
+        let_assert!(Some(header) = link_template_header(&hal, &LinkHeaders::All));
+        let_assert!(Ok(links) = parse_link_template_header(&header));
+        check!(links == [("search".to_string(), search)]);
+    }
+
+    #[test]
+    fn invalid_link_templates() {
+        for value in [
+            "/users{?q}; rel=\"search\"",
+            "\"/users{?q}\"",
+            "\"/users{?q}\"; rel=\"search\",",
+            "\"/users{?q}; rel=\"search\"",
+            "\"/users{?q}\"; Rel=\"search\"",
+            "\"/users{?q}\"; rel=\"search\" \"/other\"",
+            "\"/users{?q}\"; rel=?2",
+            "\"/users\\{?q}\"; rel=search",
+            "\"/users{?q}\"; rel=%\"%C3%AB\"",
+            "\"/users{?q}\"; rel=%\"%ff\"",
+            "(\"/users\"); rel=search",
+            "\"Zoë\"; rel=search",
+        ] {
+            let_assert!(
+                Err(Error::InvalidLinkHeader(_)) = parse_link_template_header(value),
+                "{value}"
+            );
+        }
+
+        let_assert!(
+            Err(Error::InvalidUriTemplate { .. }) =
+                parse_link_template_header("\"/users{?q\"; rel=search")
+        );
+
+        let_assert!(Ok(links) = parse_link_template_header(""));
+        check!(links.is_empty());
+    }
+
+    #[test]
+    fn parse_links() {
+        let_assert!(
+            Ok(links) = parse_link_header(
+                "</users?page=2>; rel=\"next\", </users/1>;rel=item;title=\"Graham\";\
+                 type=\"application/hal+json\";hreflang=en;name=graham;anchor=\"#users\";\
+                 deprecated"
+            )
+        );
+
+        check!(
+            links
+                == [
+                    ("next".to_string(), Link::new("/users?page=2")),
+                    (
+                        "item".to_string(),
+                        Link {
+                            title: Some("Graham".to_string()),
+                            r#type: Some("application/hal+json".to_string()),
+                            hreflang: Some("en".to_string()),
+                            name: Some("graham".to_string()),
+                            extensions: [
+                                ("anchor".to_string(), Value::from("#users")),
+                                ("deprecated".to_string(), Value::Bool(true)),
+                            ]
+                            .into(),
+                            ..Link::new("/users/1")
+                        }
+                    ),
+                ]
+        );
+    }
+
+    #[test]
+    fn parse_multiple_relations() {
+        let_assert!(
+            Ok(links) = parse_link_header(
+                "</users/1>; rel=\"Self  CANONICAL https://example.org/Rel/User\"; title=Graham"
+            )
+        );
+
+        let rels = links
+            .iter()
+            .map(|(rel, _)| rel.as_str())
+            .collect::<Vec<_>>();
+        check!(rels == ["self", "canonical", "https://example.org/Rel/User"]);
+        for (_, link) in &links {
+            check!(*link == Link::new("/users/1").with_title("Graham"));
+        }
+    }
+
+    #[test]
+    fn parse_quoted_strings() {
+        let_assert!(
+            Ok(links) = parse_link_header(
+                "</a>; rel=\"next\"; title=\"Say \\\"hi\\\", then \\\\ leave; now\"; \
+                 tags=\"a,b\tc\", </b>; rel=prev; title=\"Zoë\""
+            )
+        );
+
+        check!(links.len() == 2);
+        check!(links[0].1.title.as_deref() == Some("Say \"hi\", then \\ leave; now"));
+        check!(links[0].1.extensions["tags"] == "a,b\tc");
+        check!(links[1].1.title.as_deref() == Some("Zoë"));
+    }
+
+    #[test]
+    fn parse_extended_values() {
+        let_assert!(
+            Ok(links) = parse_link_header(
+                "</a>; rel=next; title=\"Plain\"; title*=UTF-8'de'n%c3%a4chstes%20Kapitel, \
+                 </b>; rel=prev; title*=utf-8''%E2%82%AC; title=\"Plain\"; title*=UTF-8''Later, \
+                 </c>; rel=up; title*=ISO-8859-1'en'%A3%20rates; label*=UTF-8''Z%C3%B6e"
+            )
+        );
+
+        let titles = links
+            .iter()
+            .map(|(_, link)| link.title.as_deref())
+            .collect::<Vec<_>>();
+        check!(titles == [Some("nächstes Kapitel"), Some("€"), Some("£ rates")]);
+        check!(links[2].1.extensions["label"] == "Zöe");
+    }
+
+    #[test]
+    fn parse_first_param_wins() {
+        let_assert!(
+            Ok(links) = parse_link_header("</a>; rel=next; REL=prev; title=One; Title=Two")
+        );
+
+        check!(links == [("next".to_string(), Link::new("/a").with_title("One"))]);
+    }
+
+    #[test]
+    fn parse_whitespace_and_commas() {
+        let_assert!(
+            Ok(links) = parse_link_header(
+                " ,\t</a,b;c> ;\trel = \"next\" ; title =\t\"x\" ,, </d> ; rel=prev , "
+            )
+        );
+
+        check!(
+            links
+                == [
+                    ("next".to_string(), Link::new("/a,b;c").with_title("x")),
+                    ("prev".to_string(), Link::new("/d")),
+                ]
+        );
+
+        let_assert!(Ok(links) = parse_link_header(""));
+        check!(links.is_empty());
+    }
+
+    #[test]
+    fn links_round_trip() {
+        let hal = Hal::new(())
+            .with_link("self", "/users?page=2")
+            .with_link(
+                "item",
+                Link::new("/users/1")
+                    .with_title("Zoë \"Z\" Smith")
+                    .with_type("application/hal+json"),
+            )
+            .with_link("item", Link::new("/users/2").with_title("Back\\slash"));
+
+        let_assert!(Some(header) = link_header(&hal, &LinkHeaders::All));
+        let_assert!(Ok(links) = parse_link_header(&header));
+
+        let parsed = Hal::new(()).with_links(links);
+        check!(parsed.links == hal.links);
+    }
+
+    #[test]
+    fn merge_into_document() {
+        let_assert!(
+            Ok(links) = parse_link_header("</users?page=3>; rel=next, </users/3>; rel=item")
+        );
+
+        let hal = Hal::new(())
+            .with_link("self", "/users?page=2")
+            .with_link("item", "/users/2")
+            .with_links(links);
+
+        check!(hal.links.len() == 3);
+        let_assert!(Some(items) = hal.links.get("item"));
+        let hrefs = items
+            .iter()
+            .map(|link| link.href.as_str())
+            .collect::<Vec<_>>();
+        check!(hrefs == ["/users/2", "/users/3"]);
+    }
+
+    #[test]
+    fn invalid_links() {
+        for value in [
+            "/users; rel=next",
+            "</users; rel=next",
+            "</users>",
+            "</users>; title=\"No relation\"",
+            "</users>; rel",
+            "</users>; rel=next </other>; rel=prev",
+            "</users>; rel=next; =x",
+            "</users>; rel=next; title=",
+            "</users>; rel=next; title=\"unterminated",
+            "</users>; rel=next; title=\"bad\u{7}character\"",
+            "</users>; rel=next; title=\"bad escape\\\u{0}\"",
+            "</users>; rel=next; title=a b",
+            "</users>; rel=next; title*=UTF-8%20no%20quotes",
+            "</users>; rel=next; title*=UTF-8''%e2%82",
+            "</users>; rel=next; title*=UTF-8''%zz",
+            "</users>; rel=next; title*=KOI8-R''abc",
+            "</users>; rel=next; title*",
+        ] {
+            let_assert!(
+                Err(Error::InvalidLinkHeader(_)) = parse_link_header(value),
+                "{value}"
+            );
+        }
+    }
+}
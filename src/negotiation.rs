@@ -0,0 +1,352 @@
+use std::{convert::Infallible, str::FromStr};
+
+use http::{header::ACCEPT, HeaderMap};
+
+use crate::Hal;
+
+/// The representations that a HAL document can be returned as.
+///
+/// Some representations are only available with optional features, such as HAL+XML with the `xml`
+/// feature, so the set of variants can grow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+#[display("{}", self.media_type())]
+#[non_exhaustive]
+pub enum Representation {
+    /// The full HAL-FORMS document, including templates.
+    HalForms,
+    /// A plain HAL document, with any templates removed.
+    Hal,
+    /// Only the payload of the document, without any links, embedded documents or templates.
+    Json,
+    /// A Siren entity converted from the full document.
+    Siren,
+    /// A Collection+JSON document, for documents that are collections of embedded items.
+    CollectionJson,
+    /// The full document as HAL+XML, for clients that can't consume JSON.
+    #[cfg(feature = "xml")]
+    HalXml,
+    /// An HTML page rendering the full document, for browsing the API by hand.
+    Html,
+}
+
+impl Representation {
+    /// The media type used for this representation.
+    #[must_use]
+    pub const fn media_type(self) -> &'static str {
+        match self {
+            Self::HalForms => "application/prs.hal-forms+json",
+            Self::Hal => "application/hal+json",
+            Self::Json => "application/json",
+            Self::Siren => "application/vnd.siren+json",
+            Self::CollectionJson => "application/vnd.collection+json",
+            #[cfg(feature = "xml")]
+            Self::HalXml => "application/hal+xml",
+            Self::Html => "text/html",
+        }
+    }
+
+    /// The `Content-Type` header value used for this representation.
+    ///
+    /// This is the media type, along with the charset for HTML since it otherwise defaults to
+    /// something other than UTF-8.
+    #[must_use]
+    pub const fn content_type(self) -> &'static str {
+        match self {
+            Self::Html => "text/html; charset=utf-8",
+            _ => self.media_type(),
+        }
+    }
+
+    /// The representations that a HAL document can be returned as, in order of preference.
+    ///
+    /// The most preferred is the richest representation that the document needs, so that a
+    /// document with templates is HAL-FORMS and a document with only links is HAL. Siren and HTML
+    /// are always the least preferred, so that they're only used by clients that ask for them
+    /// specifically.
+    #[must_use]
+    pub fn preferences<P>(hal: &Hal<P>) -> [Self; 5] {
+        if has_templates(hal) {
+            [
+                Self::HalForms,
+                Self::Hal,
+                Self::Json,
+                Self::Siren,
+                Self::Html,
+            ]
+        } else if !hal.links.is_empty() || !hal.embedded.is_empty() {
+            [
+                Self::Hal,
+                Self::HalForms,
+                Self::Json,
+                Self::Siren,
+                Self::Html,
+            ]
+        } else {
+            [
+                Self::Json,
+                Self::Hal,
+                Self::HalForms,
+                Self::Siren,
+                Self::Html,
+            ]
+        }
+    }
+}
+
+/// The media ranges accepted by a client, as given in the `Accept` header of the request.
+///
+/// Media ranges that can't be parsed are ignored. If no media ranges are given then everything is
+/// acceptable, as if the header was `*/*`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Accept {
+    ranges: Vec<MediaRange>,
+}
+
+/// A single media range from an `Accept` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MediaRange {
+    r#type:  String,
+    subtype: String,
+    /// The quality of the range, in thousandths.
+    quality: u16,
+}
+
+impl Accept {
+    /// Build the accepted media ranges from all of the `Accept` headers in a request.
+    #[must_use]
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let ranges = headers
+            .get_all(ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(MediaRange::parse)
+            .collect();
+
+        Self { ranges }
+    }
+
+    /// Determine how acceptable a media type is, in thousandths.
+    ///
+    /// This is the quality of the most specific media range that matches the media type, or zero
+    /// if none of them do.
+    #[must_use]
+    pub fn quality(&self, media_type: &str) -> u16 {
+        if self.ranges.is_empty() {
+            return 1000;
+        }
+
+        let (r#type, subtype) = media_type.split_once('/').unwrap_or((media_type, ""));
+
+        self.ranges
+            .iter()
+            .filter_map(|range| {
+                range
+                    .specificity(r#type, subtype)
+                    .map(|s| (s, range.quality))
+            })
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(0, |(_, quality)| quality)
+    }
+
+    /// Select the best representation from those available, which are given in order of
+    /// preference.
+    ///
+    /// Returns `None` if none of the representations are acceptable.
+    #[must_use]
+    pub fn negotiate(&self, available: &[Representation]) -> Option<Representation> {
+        available
+            .iter()
+            .map(|representation| (*representation, self.quality(representation.media_type())))
+            .filter(|(_, quality)| *quality > 0)
+            .fold(
+                None,
+                |best: Option<(Representation, u16)>, candidate| match best {
+                    Some(best) if best.1 >= candidate.1 => Some(best),
+                    _ => Some(candidate),
+                },
+            )
+            .map(|(representation, _)| representation)
+    }
+}
+
+impl FromStr for Accept {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            ranges: s.split(',').filter_map(MediaRange::parse).collect(),
+        })
+    }
+}
+
+impl MediaRange {
+    /// Parse a single media range, such as `application/hal+json;q=0.5`.
+    fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split(';').map(str::trim);
+
+        let (r#type, subtype) = parts.next()?.split_once('/')?;
+        if r#type.is_empty() || subtype.is_empty() || (r#type == "*" && subtype != "*") {
+            return None;
+        }
+
+        let mut quality = 1000;
+        for param in parts {
+            if let Some((name, value)) = param.split_once('=') {
+                if name.trim().eq_ignore_ascii_case("q") {
+                    quality = parse_quality(value.trim())?;
+                }
+            }
+        }
+
+        Some(Self {
+            r#type: r#type.to_ascii_lowercase(),
+            subtype: subtype.to_ascii_lowercase(),
+            quality,
+        })
+    }
+
+    /// How specifically this range matches a media type, or `None` if it doesn't match at all.
+    fn specificity(&self, r#type: &str, subtype: &str) -> Option<u8> {
+        if self.r#type == "*" {
+            Some(0)
+        } else if !self.r#type.eq_ignore_ascii_case(r#type) {
+            None
+        } else if self.subtype == "*" {
+            Some(1)
+        } else if self.subtype.eq_ignore_ascii_case(subtype) {
+            Some(2)
+        } else {
+            None
+        }
+    }
+}
+
+/// Parse a quality value, as defined in RFC 9110, into thousandths.
+fn parse_quality(value: &str) -> Option<u16> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > 3 || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let fraction = format!("{fraction:0<3}").parse::<u16>().ok()?;
+    match whole {
+        "0" => Some(fraction),
+        "1" if fraction == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+/// Helper to see if the HAL document or any documented embedded into it have any templated values.
+fn has_templates<P>(hal: &Hal<P>) -> bool {
+    if hal.templates.is_empty() {
+        hal.embedded
+            .iter()
+            .flat_map(|e| e.1.iter())
+            .any(has_templates)
+    } else {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::check;
+    use http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn parse_quality_values() {
+        check!(parse_quality("1") == Some(1000));
+        check!(parse_quality("1.000") == Some(1000));
+        check!(parse_quality("0.5") == Some(500));
+        check!(parse_quality("0.125") == Some(125));
+        check!(parse_quality("0") == Some(0));
+        check!(parse_quality("1.5").is_none());
+        check!(parse_quality("0.1234").is_none());
+        check!(parse_quality("abc").is_none());
+    }
+
+    #[test]
+    fn quality() {
+        let accept: Accept = "application/*;q=0.5, application/json, text/html;q=0, */*;q=0.1"
+            .parse()
+            .unwrap();
+
+        check!(accept.quality("application/json") == 1000);
+        check!(accept.quality("application/hal+json") == 500);
+        check!(accept.quality("text/html") == 0);
+        check!(accept.quality("image/png") == 100);
+    }
+
+    #[test]
+    fn empty_accepts_everything() {
+        check!(Accept::default().quality("application/json") == 1000);
+        check!(Accept::from_headers(&HeaderMap::new()).quality("text/html") == 1000);
+    }
+
+    #[test]
+    fn from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append(ACCEPT, HeaderValue::from_static("text/html"));
+        headers.append(
+            ACCEPT,
+            HeaderValue::from_static("application/json;q=0.5, invalid"),
+        );
+
+        let accept = Accept::from_headers(&headers);
+        check!(accept.quality("text/html") == 1000);
+        check!(accept.quality("application/json") == 500);
+        check!(accept.quality("application/hal+json") == 0);
+    }
+
+    #[test]
+    fn negotiate() {
+        let available = [
+            Representation::HalForms,
+            Representation::Hal,
+            Representation::Json,
+        ];
+
+        let negotiate = |header: &str| header.parse::<Accept>().unwrap().negotiate(&available);
+
+        check!(negotiate("*/*") == Some(Representation::HalForms));
+        check!(negotiate("application/json") == Some(Representation::Json));
+        check!(negotiate("application/hal+json, application/json") == Some(Representation::Hal));
+        check!(
+            negotiate("application/json, application/hal+json;q=0.9") == Some(Representation::Json)
+        );
+        check!(negotiate("application/*") == Some(Representation::HalForms));
+        check!(negotiate("text/html") == None);
+    }
+
+    #[test]
+    fn negotiate_html() {
+        let available = Representation::preferences(&Hal::new(()).with_link("self", "/"));
+
+        let negotiate = |header: &str| header.parse::<Accept>().unwrap().negotiate(&available);
+
+        check!(negotiate("*/*") == Some(Representation::Hal));
+        check!(negotiate("text/html") == Some(Representation::Html));
+        check!(
+            negotiate("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
+                == Some(Representation::Html)
+        );
+        check!(negotiate("application/json, text/html;q=0.5") == Some(Representation::Json));
+    }
+
+    #[test]
+    fn negotiate_siren() {
+        let available = Representation::preferences(&Hal::new(()).with_link("self", "/"));
+
+        let negotiate = |header: &str| header.parse::<Accept>().unwrap().negotiate(&available);
+
+        check!(negotiate("application/vnd.siren+json") == Some(Representation::Siren));
+        check!(negotiate("application/*") == Some(Representation::Hal));
+        check!(
+            negotiate("application/hal+json;q=0.5, application/vnd.siren+json")
+                == Some(Representation::Siren)
+        );
+    }
+}
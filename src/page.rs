@@ -0,0 +1,350 @@
+use serde::Serialize;
+
+use crate::{values::LinkRelation, Error, Hal, Link, UriTemplate};
+
+/// A single page of a larger collection of items, which can be rendered as a HAL document with
+/// links to navigate between the pages.
+///
+/// The href is a URI Template with `page` and `size` variables, such as `/users{?page,size}`,
+/// which is used as the templated self link and expanded to build the navigation links. Pages are
+/// numbered from 1.
+/// The items can be anything that converts into a HAL document, including any type that derives
+/// `HalResource`.
+#[derive(Debug)]
+pub struct Page<T> {
+    pub href:      String,
+    pub items:     Vec<T>,
+    /// The link relation that the items are embedded under.
+    pub items_rel: String,
+    pub page:      u64,
+    pub size:      u64,
+    /// The total number of items across all pages, if it is known.
+    pub total:     Option<u64>,
+}
+
+/// The payload of a HAL document built from a page of items.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PageMetadata {
+    pub page: u64,
+
+    pub size: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+}
+
+/// The variables used to expand the href of a page.
+#[derive(Serialize)]
+struct PageVariables {
+    page: u64,
+    size: u64,
+}
+
+impl<T> Page<T> {
+    /// Create a new page of items, embedded under the `item` link relation.
+    #[must_use]
+    pub fn new<H, I>(href: H, items: I, page: u64, size: u64) -> Self
+    where
+        H: ToString,
+        I: IntoIterator<Item = T>,
+    {
+        Self {
+            href: href.to_string(),
+            items: items.into_iter().collect(),
+            items_rel: LinkRelation::Item.to_string(),
+            page,
+            size,
+            total: None,
+        }
+    }
+
+    /// Set the link relation that the items are embedded under.
+    #[must_use]
+    pub fn with_items_rel<S>(mut self, value: S) -> Self
+    where
+        S: ToString,
+    {
+        self.items_rel = value.to_string();
+
+        self
+    }
+
+    /// Set the total number of items across all pages.
+    #[must_use]
+    pub fn with_total(mut self, value: u64) -> Self {
+        self.total = Some(value);
+
+        self
+    }
+
+    /// The number of the last page, if the total number of items is known.
+    ///
+    /// There is always at least one page, even if there are no items at all.
+    #[must_use]
+    pub fn last_page(&self) -> Option<u64> {
+        self.total
+            .map(|total| total.div_ceil(self.size.max(1)).max(1))
+    }
+
+    /// Whether there is a page after this one.
+    ///
+    /// If the total number of items isn't known then there is assumed to be another page whenever
+    /// this one is full.
+    #[must_use]
+    pub fn has_next(&self) -> bool {
+        match self.last_page() {
+            Some(last) => self.page < last,
+            None => self.items.len() as u64 >= self.size && self.size > 0,
+        }
+    }
+
+    /// Convert the page into a HAL document.
+    ///
+    /// The items are embedded under the configured link relation as an array, even when there is
+    /// only one item or none at all, and the payload holds the `page`, `size` and `total` of the
+    /// page. The self link is the templated href, and the navigation links are expanded from it.
+    /// The self link and the `first` link are always present, `prev` and `next` are only
+    /// present when there is such a page, and `last` is only present when the total number of
+    /// items is known.
+    ///
+    /// # Errors
+    /// If the href is not a valid URI Template.
+    pub fn into_hal<P>(self) -> Result<Hal<PageMetadata>, Error>
+    where
+        T: Into<Hal<P>>,
+        P: Serialize + Send + Sync + 'static,
+    {
+        let template = UriTemplate::parse(&self.href)?;
+        let href = |page| {
+            template.expand(&PageVariables {
+                page,
+                size: self.size,
+            })
+        };
+
+        let last = self.last_page();
+        let mut hal = Hal::new(PageMetadata {
+            page:  self.page,
+            size:  self.size,
+            total: self.total,
+        })
+        .with_link(LinkRelation::SelfLink, Link::new(&self.href).templated())
+        .with_link(LinkRelation::First, href(1)?);
+
+        if self.page > 1 {
+            // Stepping back from beyond the end goes to the last page, rather than an empty one.
+            let prev = last.map_or(self.page - 1, |last| (self.page - 1).min(last));
+            hal = hal.with_link(LinkRelation::Prev, href(prev)?);
+        }
+        if self.has_next() {
+            hal = hal.with_link(LinkRelation::Next, href(self.page + 1)?);
+        }
+        if let Some(last) = last {
+            hal = hal.with_link(LinkRelation::Last, href(last)?);
+        }
+
+        Ok(hal.with_embedded_array(self.items_rel, self.items))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+    use insta::assert_json_snapshot;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Build a page of users, with the given numbers as their IDs.
+    fn users(ids: &[u64], page: u64) -> Page<Hal> {
+        let items = ids
+            .iter()
+            .map(|id| Hal::new(json!({"id": id})).with_link("self", format!("/users/{id}")));
+
+        Page::new("/users{?page,size}", items, page, 2).with_items_rel("users")
+    }
+
+    /// Get the hrefs of the navigation links of a document, in the order first, prev, next, last.
+    fn navigation<P>(hal: &Hal<P>) -> [Option<&str>; 4] {
+        ["first", "prev", "next", "last"].map(|rel| {
+            hal.links
+                .get(rel)
+                .and_then(|links| links.iter().next())
+                .map(|link| link.href.as_str())
+        })
+    }
+
+    #[test]
+    fn middle_page() {
+        let_assert!(Ok(hal) = users(&[3, 4], 2).with_total(5).into_hal());
+
+        assert_json_snapshot!(hal, @r###"
+        {
+          "_links": {
+            "first": {
+              "href": "/users?page=1&size=2"
+            },
+            "last": {
+              "href": "/users?page=3&size=2"
+            },
+            "next": {
+              "href": "/users?page=3&size=2"
+            },
+            "prev": {
+              "href": "/users?page=1&size=2"
+            },
+            "self": {
+              "href": "/users{?page,size}",
+              "templated": true
+            }
+          },
+          "_embedded": {
+            "users": [
+              {
+                "_links": {
+                  "self": {
+                    "href": "/users/3"
+                  }
+                },
+                "id": 3
+              },
+              {
+                "_links": {
+                  "self": {
+                    "href": "/users/4"
+                  }
+                },
+                "id": 4
+              }
+            ]
+          },
+          "page": 2,
+          "size": 2,
+          "total": 5
+        }
+        "###);
+    }
+
+    #[test]
+    fn first_page() {
+        let_assert!(Ok(hal) = users(&[1, 2], 1).with_total(5).into_hal());
+
+        check!(
+            navigation(&hal)
+                == [
+                    Some("/users?page=1&size=2"),
+                    None,
+                    Some("/users?page=2&size=2"),
+                    Some("/users?page=3&size=2"),
+                ]
+        );
+    }
+
+    #[test]
+    fn last_page() {
+        let_assert!(Ok(hal) = users(&[5], 3).with_total(5).into_hal());
+
+        check!(
+            navigation(&hal)
+                == [
+                    Some("/users?page=1&size=2"),
+                    Some("/users?page=2&size=2"),
+                    None,
+                    Some("/users?page=3&size=2"),
+                ]
+        );
+    }
+
+    #[test]
+    fn beyond_last_page() {
+        let_assert!(Ok(hal) = users(&[], 7).with_total(5).into_hal());
+
+        check!(
+            navigation(&hal)
+                == [
+                    Some("/users?page=1&size=2"),
+                    Some("/users?page=3&size=2"),
+                    None,
+                    Some("/users?page=3&size=2"),
+                ]
+        );
+    }
+
+    #[test]
+    fn empty_collection() {
+        let_assert!(Ok(hal) = users(&[], 1).with_total(0).into_hal());
+
+        let_assert!(Ok(value) = serde_json::to_value(&hal));
+        check!(value["_embedded"] == json!({"users": []}));
+        check!(
+            navigation(&hal)
+                == [
+                    Some("/users?page=1&size=2"),
+                    None,
+                    None,
+                    Some("/users?page=1&size=2"),
+                ]
+        );
+    }
+
+    #[test]
+    fn unknown_total() {
+        let_assert!(Ok(full) = users(&[3, 4], 2).into_hal());
+        check!(full.payload().total == None);
+        check!(
+            navigation(&full)
+                == [
+                    Some("/users?page=1&size=2"),
+                    Some("/users?page=1&size=2"),
+                    Some("/users?page=3&size=2"),
+                    None,
+                ]
+        );
+
+        let_assert!(Ok(partial) = users(&[5], 3).into_hal());
+        check!(
+            navigation(&partial)
+                == [
+                    Some("/users?page=1&size=2"),
+                    Some("/users?page=2&size=2"),
+                    None,
+                    None,
+                ]
+        );
+    }
+
+    #[test]
+    fn single_item() {
+        let_assert!(Ok(hal) = users(&[5], 3).with_total(5).into_hal());
+
+        let_assert!(Ok(value) = serde_json::to_value(&hal));
+        check!(value["_embedded"]["users"][0]["id"] == 5);
+        check!(value["_embedded"]["users"].as_array().map(Vec::len) == Some(1));
+    }
+
+    #[test]
+    fn into_hal_items() {
+        struct User {
+            id: u64,
+        }
+
+        impl From<User> for Hal {
+            fn from(user: User) -> Self {
+                Hal::new(json!({"id": user.id}))
+            }
+        }
+
+        let page = Page::new("/users{?page,size}", [User { id: 1 }, User { id: 2 }], 1, 2);
+        let_assert!(Ok(hal) = page.into_hal());
+
+        let_assert!(Some(items) = hal.embedded.get("item"));
+        check!(items.iter().count() == 2);
+    }
+
+    #[test]
+    fn invalid_href() {
+        let page = Page::<Hal>::new("/users{?page", vec![], 1, 10);
+
+        let_assert!(Err(Error::InvalidUriTemplate { .. }) = page.into_hal());
+    }
+}
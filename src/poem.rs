@@ -0,0 +1,223 @@
+mod accept;
+
+use poem::{error::ResponseError, http::StatusCode, IntoResponse, Response};
+use serde::Serialize;
+
+use crate::{Error, HalResponse};
+
+impl<P> IntoResponse for HalResponse<P>
+where
+    P: Serialize + Send,
+{
+    fn into_response(self) -> Response {
+        let response = match self.into_http_response::<Vec<u8>>() {
+            Ok(response) => response,
+            Err(e) => return e.as_response(),
+        };
+
+        let (parts, body) = response.into_parts();
+
+        let mut result = Response::builder().status(parts.status).body(body);
+        *result.headers_mut() = parts.headers;
+
+        result
+    }
+}
+
+impl ResponseError for Error {
+    fn status(&self) -> StatusCode {
+        match self {
+            // Cursors come from the client, so a bad one is the client's fault.
+            Self::InvalidCursor => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn as_response(&self) -> Response {
+        Response::builder().status(self.status()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use assert2::check;
+    use headers::{CacheControl, ContentType, ETag};
+    use insta::assert_json_snapshot;
+    use poem::{handler, http::Uri, Endpoint, Request};
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{Accept, Hal};
+
+    async fn call<E>(endpoint: E, accept: Option<&str>) -> (Response, Value)
+    where
+        E: Endpoint,
+    {
+        let mut request = Request::builder().uri(Uri::from_static("/test"));
+        if let Some(accept) = accept {
+            request = request.header("accept", accept);
+        }
+
+        let response = endpoint.get_response(request.finish()).await;
+        let (parts, body) = response.into_parts();
+        let body = body.into_vec().await.unwrap();
+        let body = if body.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&body).unwrap()
+        };
+
+        (Response::from_parts(parts, poem::Body::empty()), body)
+    }
+
+    #[tokio::test]
+    async fn hal_document() {
+        #[handler]
+        fn endpoint() -> HalResponse {
+            crate::new(json!({"currentlyProcessing": 14}))
+                .with_link("self", "/orders")
+                .with_embedded(
+                    "orders",
+                    Hal::new(json!({"total": 30.00})).with_link("self", "/orders/123"),
+                )
+        }
+
+        let (response, body) = call(endpoint, None).await;
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.content_type() == Some("application/hal+json"));
+        assert_json_snapshot!(body, @r###"
+        {
+          "_links": {
+            "self": {
+              "href": "/orders"
+            }
+          },
+          "_embedded": {
+            "orders": {
+              "_links": {
+                "self": {
+                  "href": "/orders/123"
+                }
+              },
+              "total": 30.0
+            }
+          },
+          "currentlyProcessing": 14
+        }
+        "###);
+    }
+
+    #[tokio::test]
+    async fn with_nested_template() {
+        #[handler]
+        fn endpoint() -> HalResponse<()> {
+            crate::new(()).with_embedded(
+                "other",
+                Hal::new(()).with_template("default", crate::Template::default()),
+            )
+        }
+
+        let (response, body) = call(endpoint, None).await;
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.content_type() == Some("application/prs.hal-forms+json"));
+        assert_json_snapshot!(body, @r###"
+        {
+          "_embedded": {
+            "other": {
+              "_templates": {
+                "default": {}
+              }
+            }
+          }
+        }
+        "###);
+    }
+
+    #[tokio::test]
+    async fn status_code() {
+        #[handler]
+        fn endpoint() -> HalResponse<()> {
+            crate::new(()).with_status_code(StatusCode::ACCEPTED)
+        }
+
+        let (response, body) = call(endpoint, None).await;
+
+        check!(response.status() == StatusCode::ACCEPTED);
+        check!(response.content_type() == Some("application/json"));
+        assert_json_snapshot!(body, @r###"{}"###);
+    }
+
+    #[tokio::test]
+    async fn headers() {
+        #[handler]
+        fn endpoint() -> HalResponse<()> {
+            crate::new(())
+                .with_header(
+                    CacheControl::new()
+                        .with_public()
+                        .with_max_age(std::time::Duration::from_secs(3600)),
+                )
+                .with_header(ETag::from_str("\"Hello\"").unwrap())
+                .with_header(ContentType::xml())
+        }
+
+        let (response, body) = call(endpoint, None).await;
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.content_type() == Some("application/json"));
+        check!(response.headers()["cache-control"] == "public, max-age=3600");
+        check!(response.headers()["etag"] == "\"Hello\"");
+        assert_json_snapshot!(body, @r###"{}"###);
+    }
+
+    #[tokio::test]
+    async fn invalid_payload() {
+        #[handler]
+        fn endpoint() -> Result<HalResponse, Error> {
+            crate::try_new(json!({"_links": "oops"}))
+        }
+
+        let (response, _) = call(endpoint, None).await;
+
+        check!(response.status() == StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn invalid_cursor() {
+        #[handler]
+        fn endpoint() -> Result<HalResponse, Error> {
+            crate::CursorQuery::from_query("cursor=tampered")
+                .decode::<u64>(&crate::CursorCodec::default())
+                .map(|_| crate::new(json!({})))
+        }
+
+        let (response, _) = call(endpoint, None).await;
+
+        check!(response.status() == StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn negotiate() {
+        #[handler]
+        fn endpoint(accept: Accept) -> HalResponse {
+            crate::new(json!({"name": "Graham"}))
+                .with_link("self", "/test")
+                .with_accept(accept)
+        }
+
+        let (response, body) = call(endpoint, Some("application/json")).await;
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.content_type() == Some("application/json"));
+        check!(response.headers()["vary"] == "accept");
+        check!(body == json!({"name": "Graham"}));
+
+        let (response, _) = call(endpoint, Some("image/png")).await;
+
+        check!(response.status() == StatusCode::NOT_ACCEPTABLE);
+    }
+}
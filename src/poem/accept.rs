@@ -0,0 +1,9 @@
+use poem::{FromRequest, Request, RequestBody, Result};
+
+use crate::Accept;
+
+impl<'a> FromRequest<'a> for Accept {
+    async fn from_request(req: &'a Request, _body: &mut RequestBody) -> Result<Self> {
+        Ok(Self::from_headers(req.headers()))
+    }
+}
@@ -0,0 +1,215 @@
+use serde::Serialize;
+
+use crate::Hal;
+
+/// Trait for domain types that can be rendered as a HAL document.
+///
+/// This is normally implemented using `#[derive(HalResource)]`, which requires the `derive`
+/// feature. The derive also implements `From<T> for Hal<T::Payload>`, so that derived types can be
+/// used anywhere that takes `Into<Hal>`, such as [`Hal::with_embedded`] and [`crate::Page`].
+pub trait HalResource {
+    /// The type of the payload in the HAL document.
+    type Payload: Serialize;
+
+    /// Convert this value into a HAL document.
+    fn into_hal(self) -> Hal<Self::Payload>;
+}
+
+impl<P> HalResource for Hal<P>
+where
+    P: Serialize,
+{
+    type Payload = P;
+
+    fn into_hal(self) -> Hal<P> {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::check;
+    use http_halforms_derive::HalResource;
+    use insta::assert_json_snapshot;
+    use serde::Serialize;
+
+    use super::*;
+
+    #[derive(HalResource)]
+    #[hal(self_href = "/orders/{id}")]
+    #[serde(rename_all = "camelCase")]
+    struct Order {
+        id:          u32,
+        total_price: f32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        notes:       Option<String>,
+        #[hal(link = "customer", href = "/customers/{}")]
+        customer_id: String,
+        #[hal(link = "invoice", href = "/orders/{id}/invoices/{}")]
+        invoice_id:  Option<u32>,
+        #[hal(embedded = "lines")]
+        lines:       Vec<OrderLine>,
+        #[hal(skip)]
+        #[allow(dead_code)]
+        secret:      String,
+    }
+
+    #[derive(HalResource)]
+    #[hal(self_href = "/products/{product}")]
+    struct OrderLine {
+        product:  String,
+        quantity: u32,
+    }
+
+    #[derive(HalResource)]
+    struct Wrapper<T> {
+        value:   T,
+        #[hal(embedded = "extra")]
+        extra:   Option<Hal>,
+        #[hal(link = "related", href = "/related/{}")]
+        related: Vec<u32>,
+    }
+
+    #[test]
+    fn derive_resource() {
+        let order = Order {
+            id:          1,
+            total_price: 12.5,
+            notes:       None,
+            customer_id: "abc".to_owned(),
+            invoice_id:  Some(7),
+            lines:       vec![
+                OrderLine {
+                    product:  "widget".to_owned(),
+                    quantity: 2,
+                },
+                OrderLine {
+                    product:  "gadget".to_owned(),
+                    quantity: 1,
+                },
+            ],
+            secret:      "hidden".to_owned(),
+        };
+
+        let hal = order.into_hal();
+        check!(hal.payload().id == 1);
+
+        assert_json_snapshot!(hal, @r###"
+        {
+          "_links": {
+            "customer": {
+              "href": "/customers/abc"
+            },
+            "invoice": {
+              "href": "/orders/1/invoices/7"
+            },
+            "self": {
+              "href": "/orders/1"
+            }
+          },
+          "_embedded": {
+            "lines": [
+              {
+                "_links": {
+                  "self": {
+                    "href": "/products/widget"
+                  }
+                },
+                "product": "widget",
+                "quantity": 2
+              },
+              {
+                "_links": {
+                  "self": {
+                    "href": "/products/gadget"
+                  }
+                },
+                "product": "gadget",
+                "quantity": 1
+              }
+            ]
+          },
+          "id": 1,
+          "totalPrice": 12.5
+        }
+        "###);
+    }
+
+    #[test]
+    fn derive_missing_optional_values() {
+        let order = Order {
+            id:          2,
+            total_price: 0.0,
+            notes:       Some("Leave at the door".to_owned()),
+            customer_id: "abc".to_owned(),
+            invoice_id:  None,
+            lines:       vec![],
+            secret:      "hidden".to_owned(),
+        };
+
+        assert_json_snapshot!(order.into_hal(), @r###"
+        {
+          "_links": {
+            "customer": {
+              "href": "/customers/abc"
+            },
+            "self": {
+              "href": "/orders/2"
+            }
+          },
+          "id": 2,
+          "totalPrice": 0.0,
+          "notes": "Leave at the door"
+        }
+        "###);
+    }
+
+    #[test]
+    fn derive_generic() {
+        #[derive(Serialize)]
+        struct Value {
+            name: &'static str,
+        }
+
+        let wrapper = Wrapper {
+            value:   Value { name: "test" },
+            extra:   Some(Hal::new(serde_json::json!({"answer": 42}))),
+            related: vec![1, 2],
+        };
+
+        assert_json_snapshot!(wrapper.into_hal(), @r###"
+        {
+          "_links": {
+            "related": [
+              {
+                "href": "/related/1"
+              },
+              {
+                "href": "/related/2"
+              }
+            ]
+          },
+          "_embedded": {
+            "extra": {
+              "answer": 42
+            }
+          },
+          "value": {
+            "name": "test"
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn derive_into_hal() {
+        let line = || OrderLine {
+            product:  "widget".to_string(),
+            quantity: 3,
+        };
+
+        let hal: Hal<_> = line().into();
+
+        check!(serde_json::to_value(hal).ok() == serde_json::to_value(line().into_hal()).ok());
+    }
+}
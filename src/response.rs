@@ -1,18 +1,35 @@
 use std::ops::Deref;
 
-use http::{HeaderMap, StatusCode};
+use http::{
+    header::{CONTENT_TYPE, LINK, VARY},
+    HeaderMap, HeaderValue, StatusCode,
+};
 use serde::Serialize;
+use serde_json::Value;
 
-use crate::{Hal, Link, Template};
+use crate::{
+    link_header::{link_header, link_template_header},
+    Accept, Error, Hal, Link, LinkHeaders, Representation, Template, LINK_TEMPLATE,
+};
 
 /// Representation of a HAL or HAL-FORMS response that can be returned to a client.
-pub struct HalResponse {
-    pub hal:         Hal,
-    pub status_code: StatusCode,
-    pub headers:     HeaderMap,
+pub struct HalResponse<P = Value> {
+    pub hal:              Hal<P>,
+    pub status_code:      StatusCode,
+    pub headers:          HeaderMap,
+    /// The media ranges accepted by the client, if the representation should be negotiated.
+    pub accept:           Option<Accept>,
+    /// The link relation that the items are embedded under, if the document is a collection that
+    /// can be represented as Collection+JSON.
+    pub collection_items: Option<String>,
+    /// The links that are mirrored into the `Link` header of the response, if any.
+    pub link_headers:     Option<LinkHeaders>,
+    /// The templated links that are mirrored into the `Link-Template` header of the response, if
+    /// any.
+    pub link_templates:   Option<LinkHeaders>,
 }
 
-impl HalResponse {
+impl<P> HalResponse<P> {
     /// Add a new link to a HAL document.
     #[must_use]
     pub fn with_link<N, L>(mut self, name: N, link: L) -> Self
@@ -39,10 +56,11 @@ impl HalResponse {
 
     /// Add a new embedded HAL document to the HAL document.
     #[must_use]
-    pub fn with_embedded<N, H>(mut self, name: N, value: H) -> Self
+    pub fn with_embedded<N, H, E>(mut self, name: N, value: H) -> Self
     where
         N: ToString,
-        H: Into<Hal>,
+        H: Into<Hal<E>>,
+        E: Serialize + Send + Sync + 'static,
     {
         self.hal = self.hal.with_embedded(name, value);
 
@@ -51,10 +69,11 @@ impl HalResponse {
 
     /// Add a new embedded HAL document to the HAL document.
     #[must_use]
-    pub fn maybe_with_embedded<N, H>(mut self, name: N, value: Option<H>) -> Self
+    pub fn maybe_with_embedded<N, H, E>(mut self, name: N, value: Option<H>) -> Self
     where
         N: ToString,
-        H: Into<Hal>,
+        H: Into<Hal<E>>,
+        E: Serialize + Send + Sync + 'static,
     {
         self.hal = self.hal.maybe_with_embedded(name, value);
 
@@ -115,28 +134,507 @@ impl HalResponse {
 
         self
     }
+
+    /// Negotiate the representation of the response using the media ranges accepted by the
+    /// client.
+    ///
+    /// Without this the response is always returned in the richest representation the document
+    /// needs. With it, templates are removed for clients that only accept plain HAL, only the
+    /// payload is returned for clients that only accept plain JSON, and a `406 Not Acceptable` is
+    /// returned if the client accepts none of these. Negotiated responses also have `Vary: accept`
+    /// set.
+    ///
+    /// With Axum, the `negotiate` middleware does this for every response instead, as does the
+    /// `Negotiate` handler with Salvo. With Actix Web and Poem, extract [`Accept`] in the handler
+    /// and pass it here.
+    #[must_use]
+    pub fn with_accept(mut self, accept: Accept) -> Self {
+        self.accept = Some(accept);
+
+        self
+    }
+
+    /// Mark the document as a collection of the documents embedded under the given link relation,
+    /// so that it can also be negotiated as Collection+JSON.
+    #[must_use]
+    pub fn with_collection_items<S>(mut self, rel: S) -> Self
+    where
+        S: ToString,
+    {
+        self.collection_items = Some(rel.to_string());
+
+        self
+    }
+
+    /// Mirror every link of the document into the `Link` header of the response.
+    ///
+    /// Templated links can't be represented in a `Link` header, so they are always left out. Use
+    /// [`HalResponse::with_link_templates`] to mirror them into a `Link-Template` header instead.
+    #[must_use]
+    pub fn with_link_headers(mut self) -> Self {
+        self.link_headers = Some(LinkHeaders::All);
+
+        self
+    }
+
+    /// Mirror the links with the given relation into the `Link` header of the response, along
+    /// with any others that are already mirrored.
+    #[must_use]
+    pub fn with_link_header<S>(mut self, rel: S) -> Self
+    where
+        S: ToString,
+    {
+        select(&mut self.link_headers, rel.to_string());
+
+        self
+    }
+
+    /// Mirror every templated link of the document into the `Link-Template` header of the
+    /// response.
+    ///
+    /// The base URI for the variables of a template can be given with a `var-base` extension on
+    /// the link.
+    #[must_use]
+    pub fn with_link_templates(mut self) -> Self {
+        self.link_templates = Some(LinkHeaders::All);
+
+        self
+    }
+
+    /// Mirror the templated links with the given relation into the `Link-Template` header of the
+    /// response, along with any others that are already mirrored.
+    #[must_use]
+    pub fn with_link_template<S>(mut self, rel: S) -> Self
+    where
+        S: ToString,
+    {
+        select(&mut self.link_templates, rel.to_string());
+
+        self
+    }
 }
 
-impl Deref for HalResponse {
-    type Target = Hal;
+impl<P> HalResponse<P>
+where
+    P: Serialize,
+{
+    /// Convert this into an HTTP response, with the body serialized as JSON.
+    ///
+    /// The representation of the document is negotiated if [`HalResponse::with_accept`] was
+    /// used, and the `Content-Type` header is set to match it. If the client accepts none of the
+    /// representations then the response is a `406 Not Acceptable` with an empty body and no
+    /// `Content-Type`, but with every other header. Any links
+    /// selected with [`HalResponse::with_link_headers`] or [`HalResponse::with_link_header`] are
+    /// also added as a `Link` header, and templated links selected with
+    /// [`HalResponse::with_link_templates`] or [`HalResponse::with_link_template`] as a
+    /// `Link-Template` header, whatever the representation.
+    ///
+    /// # Errors
+    /// If the document can't be serialized.
+    pub fn into_http_response<B>(self) -> Result<http::Response<B>, Error>
+    where
+        B: From<Vec<u8>>,
+    {
+        let mut available = Representation::preferences(&self.hal).to_vec();
+        if self.collection_items.is_some() {
+            // Still behind HTML, which is always the last resort.
+            available.insert(available.len() - 1, Representation::CollectionJson);
+        }
+        #[cfg(feature = "xml")]
+        available.insert(available.len() - 1, Representation::HalXml);
+
+        // Built up front, since serializing the body can consume the document.
+        let link_value = header_value(self.link_headers.as_ref(), |selection| {
+            link_header(&self.hal, selection)
+        })?;
+        let link_template_value = header_value(self.link_templates.as_ref(), |selection| {
+            link_template_header(&self.hal, selection)
+        })?;
+
+        let representation = match &self.accept {
+            None => Some(available[0]),
+            Some(accept) => accept.negotiate(&available),
+        };
+
+        let (status_code, body, content_type) = match representation {
+            None => (StatusCode::NOT_ACCEPTABLE, vec![], None),
+            Some(representation) => {
+                let body = match representation {
+                    Representation::HalForms => {
+                        serde_json::to_vec(&self.hal).map_err(Error::Serialization)?
+                    },
+                    Representation::Hal => serde_json::to_vec(&self.hal.without_templates())
+                        .map_err(Error::Serialization)?,
+                    // Wrapping the payload keeps it serialized as an object, the same as the full
+                    // document.
+                    Representation::Json => serde_json::to_vec(&Hal::new(self.hal.payload))
+                        .map_err(Error::Serialization)?,
+                    Representation::Siren => {
+                        serde_json::to_vec(&self.hal.to_siren()?).map_err(Error::Serialization)?
+                    },
+                    Representation::CollectionJson => {
+                        let items = self.collection_items.as_deref().unwrap_or_default();
+                        serde_json::to_vec(&self.hal.to_collection_json(items)?)
+                            .map_err(Error::Serialization)?
+                    },
+                    #[cfg(feature = "xml")]
+                    Representation::HalXml => self.hal.to_xml()?.into_bytes(),
+                    Representation::Html => self.hal.to_html()?.into_bytes(),
+                };
+
+                (self.status_code, body, Some(representation.content_type()))
+            },
+        };
+
+        let mut response = http::Response::new(B::from(body));
+        *response.status_mut() = status_code;
+
+        // Headers describing the resource, such as caching headers, still apply when no
+        // representation of it was acceptable, but there's no body to have a content type.
+        let headers = response.headers_mut();
+        headers.extend(self.headers);
+        match content_type {
+            Some(content_type) => {
+                headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+            },
+            None => {
+                headers.remove(CONTENT_TYPE);
+            },
+        }
+
+        if let Some(value) = link_value {
+            headers.append(LINK, value);
+        }
+        if let Some(value) = link_template_value {
+            headers.append(LINK_TEMPLATE, value);
+        }
+        if self.accept.is_some() {
+            headers.append(VARY, HeaderValue::from_static("accept"));
+        }
+
+        Ok(response)
+    }
+}
+
+impl<P> TryFrom<HalResponse<P>> for http::Response<Vec<u8>>
+where
+    P: Serialize,
+{
+    type Error = Error;
+
+    fn try_from(value: HalResponse<P>) -> Result<Self, Self::Error> {
+        value.into_http_response()
+    }
+}
+
+impl<P> Deref for HalResponse<P> {
+    type Target = Hal<P>;
 
     fn deref(&self) -> &Self::Target {
         &self.hal
     }
 }
 
+/// Add a link relation to the selection of links that are mirrored into a header.
+fn select(selection: &mut Option<LinkHeaders>, rel: String) {
+    match selection {
+        Some(LinkHeaders::All) => {},
+        Some(LinkHeaders::Only(rels)) => {
+            rels.insert(rel);
+        },
+        None => *selection = Some(LinkHeaders::Only([rel].into())),
+    }
+}
+
+/// Build the value of a header that mirrors the selected links, if there are any.
+fn header_value<F>(selection: Option<&LinkHeaders>, build: F) -> Result<Option<HeaderValue>, Error>
+where
+    F: FnOnce(&LinkHeaders) -> Option<String>,
+{
+    selection
+        .and_then(build)
+        .map(|value| {
+            HeaderValue::try_from(value).map_err(|e| Error::InvalidLinkHeader(e.to_string()))
+        })
+        .transpose()
+}
+
 /// Create a new HAL response for the given payload value.
-///
-/// # Panics
-/// This will panic if the value provided can not be serialized into JSON for some reason.
 #[must_use]
-pub fn new<V>(value: V) -> HalResponse
+pub fn new<V>(value: V) -> HalResponse<V>
 where
     V: Serialize,
 {
     HalResponse {
-        hal:         Hal::new(value),
-        status_code: StatusCode::OK,
-        headers:     HeaderMap::default(),
+        hal:              Hal::new(value),
+        status_code:      StatusCode::OK,
+        headers:          HeaderMap::default(),
+        accept:           None,
+        collection_items: None,
+        link_headers:     None,
+        link_templates:   None,
+    }
+}
+
+/// Create a new HAL response for the given payload value, checking that the payload can be
+/// represented correctly.
+///
+/// # Errors
+/// If the payload can't be serialized, doesn't serialize into a JSON object, or contains any of
+/// the keys reserved for the HAL document.
+pub fn try_new<V>(value: V) -> Result<HalResponse<V>, Error>
+where
+    V: Serialize,
+{
+    Ok(HalResponse {
+        hal:              Hal::try_new(value)?,
+        status_code:      StatusCode::OK,
+        headers:          HeaderMap::default(),
+        accept:           None,
+        collection_items: None,
+        link_headers:     None,
+        link_templates:   None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use assert2::{check, let_assert};
+    use http::{
+        header::{CACHE_CONTROL, ETAG},
+        StatusCode,
+    };
+    use insta::assert_json_snapshot;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::Template;
+
+    fn body(response: &http::Response<Vec<u8>>) -> Value {
+        serde_json::from_slice(response.body()).unwrap()
+    }
+
+    #[test]
+    fn plain_json() {
+        let_assert!(Ok(response) = http::Response::try_from(crate::new(json!({"a": 1}))));
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.headers()[CONTENT_TYPE] == "application/json");
+        check!(response.headers().get(VARY).is_none());
+        check!(body(&response) == json!({"a": 1}));
+    }
+
+    #[test]
+    fn hal_forms() {
+        let_assert!(
+            Ok(response) = crate::new(json!({"a": 1}))
+                .with_link("self", "/a")
+                .with_template("default", Template::default())
+                .with_status_code(StatusCode::CREATED)
+                .with_header("\"abc\"".parse::<headers::ETag>().unwrap())
+                .into_http_response::<Vec<u8>>()
+        );
+
+        check!(response.status() == StatusCode::CREATED);
+        check!(response.headers()[CONTENT_TYPE] == "application/prs.hal-forms+json");
+        check!(response.headers()[ETAG] == "\"abc\"");
+        assert_json_snapshot!(body(&response), @r###"
+        {
+          "_links": {
+            "self": {
+              "href": "/a"
+            }
+          },
+          "_templates": {
+            "default": {}
+          },
+          "a": 1
+        }
+        "###);
+    }
+
+    #[test]
+    fn negotiated() {
+        let_assert!(
+            Ok(response) = crate::new(json!({"a": 1}))
+                .with_link("self", "/a")
+                .with_template("default", Template::default())
+                .with_accept("application/hal+json".parse().unwrap())
+                .into_http_response::<Vec<u8>>()
+        );
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.headers()[CONTENT_TYPE] == "application/hal+json");
+        check!(response.headers()[VARY] == "accept");
+        assert_json_snapshot!(body(&response), @r###"
+        {
+          "_links": {
+            "self": {
+              "href": "/a"
+            }
+          },
+          "a": 1
+        }
+        "###);
+    }
+
+    #[test]
+    fn html() {
+        let_assert!(
+            Ok(response) = crate::new(json!({"a": 1}))
+                .with_link("self", "/a")
+                .with_accept("text/html".parse().unwrap())
+                .into_http_response::<Vec<u8>>()
+        );
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.headers()[CONTENT_TYPE] == "text/html; charset=utf-8");
+        check!(response.headers()[VARY] == "accept");
+
+        let body = String::from_utf8(response.into_body()).unwrap();
+        check!(body.starts_with("<!DOCTYPE html>"));
+        check!(body.contains("<a href=\"/a\">/a</a>"));
+    }
+
+    #[test]
+    fn collection_json() {
+        let document = || {
+            crate::new(())
+                .with_link("self", "/users")
+                .with_embedded("items", Hal::new(json!({"name": "Graham"})))
+        };
+
+        let_assert!(
+            Ok(response) = document()
+                .with_collection_items("items")
+                .with_accept("application/vnd.collection+json".parse().unwrap())
+                .into_http_response::<Vec<u8>>()
+        );
+
+        check!(response.status() == StatusCode::OK);
+        check!(response.headers()[CONTENT_TYPE] == "application/vnd.collection+json");
+        assert_json_snapshot!(body(&response), @r###"
+        {
+          "collection": {
+            "version": "1.0",
+            "href": "/users",
+            "items": [
+              {
+                "data": [
+                  {
+                    "name": "name",
+                    "value": "Graham"
+                  }
+                ]
+              }
+            ]
+          }
+        }
+        "###);
+
+        let_assert!(
+            Ok(response) = document()
+                .with_accept("application/vnd.collection+json".parse().unwrap())
+                .into_http_response::<Vec<u8>>()
+        );
+
+        check!(response.status() == StatusCode::NOT_ACCEPTABLE);
+    }
+
+    #[test]
+    fn not_acceptable() {
+        let_assert!(
+            Ok(response) = crate::new(json!({"a": 1}))
+                .with_link("self", "/a")
+                .with_link_header("self")
+                .with_header("\"abc\"".parse::<headers::ETag>().unwrap())
+                .with_header(headers::CacheControl::new().with_no_cache())
+                .with_header(headers::ContentType::xml())
+                .with_header(headers::Vary::any())
+                .with_accept("image/png".parse().unwrap())
+                .into_http_response::<Vec<u8>>()
+        );
+
+        check!(response.status() == StatusCode::NOT_ACCEPTABLE);
+        check!(response.headers().get(CONTENT_TYPE).is_none());
+        check!(response.headers()[ETAG] == "\"abc\"");
+        check!(response.headers()[CACHE_CONTROL] == "no-cache");
+        check!(response.headers()[LINK] == "</a>; rel=\"self\"");
+        let vary = response.headers().get_all(VARY).iter().collect::<Vec<_>>();
+        check!(vary == ["*", "accept"]);
+        check!(response.body().is_empty());
+    }
+
+    #[test]
+    fn serialization_failure() {
+        let payload = BTreeMap::from([(vec![1u8], 1)]);
+
+        let_assert!(
+            Err(Error::Serialization(_)) = crate::new(payload).into_http_response::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    fn link_headers() {
+        let document = || {
+            crate::new(json!({"name": "Graham"}))
+                .with_link("self", "/users/1")
+                .with_link("friends", "/users/1/friends")
+                .with_link("search", Link::new("/users{?q}").templated())
+                .with_accept("application/json".parse().unwrap())
+        };
+
+        let_assert!(Ok(response) = document().into_http_response::<Vec<u8>>());
+        check!(response.headers().get(LINK).is_none());
+
+        let_assert!(
+            Ok(response) = document()
+                .with_link_headers()
+                .into_http_response::<Vec<u8>>()
+        );
+        check!(
+            response.headers()[LINK]
+                == "</users/1/friends>; rel=\"friends\", </users/1>; rel=\"self\""
+        );
+
+        let_assert!(
+            Ok(response) = document()
+                .with_link_header("self")
+                .with_link_header("search")
+                .into_http_response::<Vec<u8>>()
+        );
+        check!(response.headers()[LINK] == "</users/1>; rel=\"self\"");
+    }
+
+    #[test]
+    fn link_templates() {
+        let document = || {
+            crate::new(())
+                .with_link("self", "/users")
+                .with_link("search", Link::new("/users{?q}").templated())
+                .with_link("user", Link::new("/users/{id}").templated())
+        };
+
+        let_assert!(
+            Ok(response) = document()
+                .with_link_headers()
+                .with_link_templates()
+                .into_http_response::<Vec<u8>>()
+        );
+        check!(response.headers()[LINK] == "</users>; rel=\"self\"");
+        check!(
+            response.headers()[LINK_TEMPLATE]
+                == "\"/users{?q}\";rel=\"search\", \"/users/{id}\";rel=\"user\""
+        );
+
+        let_assert!(
+            Ok(response) = document()
+                .with_link_template("search")
+                .into_http_response::<Vec<u8>>()
+        );
+        check!(response.headers().get(LINK).is_none());
+        check!(response.headers()[LINK_TEMPLATE] == "\"/users{?q}\";rel=\"search\"");
     }
 }
@@ -0,0 +1,321 @@
+mod negotiate;
+
+pub use negotiate::*;
+use salvo::{async_trait, http::StatusCode, Depot, Request, Response, Writer};
+use serde::Serialize;
+
+use crate::{Accept, Error, HalResponse};
+
+#[async_trait]
+impl<P> Writer for HalResponse<P>
+where
+    P: Serialize + Send,
+{
+    /// Write the response, negotiating its representation with the `Accept` header of the request
+    /// if the [`Negotiate`] handler has run and the handler hasn't already given the media ranges
+    /// to use.
+    async fn write(mut self, req: &mut Request, depot: &mut Depot, res: &mut Response) {
+        if self.accept.is_none() {
+            self.accept = depot.obtain::<Accept>().ok().cloned();
+        }
+
+        let response = match self.into_http_response::<Vec<u8>>() {
+            Ok(response) => response,
+            Err(e) => return e.write(req, depot, res).await,
+        };
+
+        let (parts, body) = response.into_parts();
+
+        res.status_code(parts.status);
+        res.headers_mut().extend(parts.headers);
+        res.body(body);
+    }
+}
+
+#[async_trait]
+impl Writer for Error {
+    async fn write(self, _req: &mut Request, _depot: &mut Depot, res: &mut Response) {
+        res.status_code(match self {
+            // Cursors come from the client, so a bad one is the client's fault.
+            Self::InvalidCursor => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use assert2::{check, let_assert};
+    use headers::{CacheControl, ContentType, ETag};
+    use insta::assert_json_snapshot;
+    use salvo::{
+        http::{
+            header::{ACCEPT, CONTENT_TYPE, VARY},
+            HeaderValue, ResBody,
+        },
+        FlowCtrl, Handler,
+    };
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{Hal, HalResponse};
+
+    async fn render<W>(writer: W) -> (Response, Value)
+    where
+        W: Writer,
+    {
+        render_with(writer, Request::new()).await
+    }
+
+    async fn render_with<W>(writer: W, request: Request) -> (Response, Value)
+    where
+        W: Writer,
+    {
+        render_in(writer, request, Depot::new()).await
+    }
+
+    async fn negotiated<W>(writer: W, mut request: Request) -> (Response, Value)
+    where
+        W: Writer,
+    {
+        let mut depot = Depot::new();
+        Negotiate
+            .handle(
+                &mut request,
+                &mut depot,
+                &mut Response::new(),
+                &mut FlowCtrl::new(vec![]),
+            )
+            .await;
+
+        render_in(writer, request, depot).await
+    }
+
+    async fn render_in<W>(writer: W, mut request: Request, mut depot: Depot) -> (Response, Value)
+    where
+        W: Writer,
+    {
+        let mut response = Response::new();
+        writer.write(&mut request, &mut depot, &mut response).await;
+
+        let body = match response.take_body() {
+            ResBody::None => Value::Null,
+            ResBody::Once(bytes) if bytes.is_empty() => Value::Null,
+            body => {
+                let_assert!(ResBody::Once(bytes) = body);
+                serde_json::from_slice(&bytes).unwrap()
+            },
+        };
+
+        (response, body)
+    }
+
+    #[tokio::test]
+    async fn hal_document() {
+        let (response, body) = render(
+            crate::new(json!({"currentlyProcessing": 14}))
+                .with_link("self", "/orders")
+                .with_embedded(
+                    "orders",
+                    Hal::new(json!({"total": 30.00})).with_link("self", "/orders/123"),
+                ),
+        )
+        .await;
+
+        check!(response.status_code == Some(StatusCode::OK));
+        check!(response.headers()[CONTENT_TYPE] == "application/hal+json");
+        assert_json_snapshot!(body, @r###"
+        {
+          "_links": {
+            "self": {
+              "href": "/orders"
+            }
+          },
+          "_embedded": {
+            "orders": {
+              "_links": {
+                "self": {
+                  "href": "/orders/123"
+                }
+              },
+              "total": 30.0
+            }
+          },
+          "currentlyProcessing": 14
+        }
+        "###);
+    }
+
+    #[tokio::test]
+    async fn with_nested_template() {
+        let (response, body) = render(crate::new(()).with_embedded(
+            "other",
+            Hal::new(()).with_template("default", crate::Template::default()),
+        ))
+        .await;
+
+        check!(response.status_code == Some(StatusCode::OK));
+        check!(response.headers()[CONTENT_TYPE] == "application/prs.hal-forms+json");
+        assert_json_snapshot!(body, @r###"
+        {
+          "_embedded": {
+            "other": {
+              "_templates": {
+                "default": {}
+              }
+            }
+          }
+        }
+        "###);
+    }
+
+    #[tokio::test]
+    async fn status_code() {
+        let (response, body) = render(crate::new(()).with_status_code(StatusCode::ACCEPTED)).await;
+
+        check!(response.status_code == Some(StatusCode::ACCEPTED));
+        check!(response.headers()[CONTENT_TYPE] == "application/json");
+        assert_json_snapshot!(body, @r###"{}"###);
+    }
+
+    #[tokio::test]
+    async fn headers() {
+        let (response, body) = render(
+            crate::new(())
+                .with_header(
+                    CacheControl::new()
+                        .with_public()
+                        .with_max_age(std::time::Duration::from_secs(3600)),
+                )
+                .with_header(ETag::from_str("\"Hello\"").unwrap())
+                .with_header(ContentType::xml()),
+        )
+        .await;
+
+        check!(response.status_code == Some(StatusCode::OK));
+        check!(response.headers()[CONTENT_TYPE] == "application/json");
+        check!(response.headers()["cache-control"] == "public, max-age=3600");
+        check!(response.headers()["etag"] == "\"Hello\"");
+        assert_json_snapshot!(body, @r###"{}"###);
+    }
+
+    fn accepting(accept: &str) -> Request {
+        let mut request = Request::new();
+        request
+            .headers_mut()
+            .insert(ACCEPT, HeaderValue::from_str(accept).unwrap());
+
+        request
+    }
+
+    fn negotiable() -> HalResponse {
+        crate::new(json!({"name": "Graham"}))
+            .with_link("self", "/test")
+            .with_template("default", crate::Template::default().with_method("PUT"))
+    }
+
+    #[tokio::test]
+    async fn negotiate_without_accept() {
+        let (response, body) = render(negotiable()).await;
+
+        check!(response.status_code == Some(StatusCode::OK));
+        check!(response.headers()[CONTENT_TYPE] == "application/prs.hal-forms+json");
+        check!(!response.headers().contains_key(VARY));
+        check!(body["_templates"]["default"]["method"] == "PUT");
+    }
+
+    #[tokio::test]
+    async fn without_negotiate_handler() {
+        let (response, body) = render_with(negotiable(), accepting("image/png")).await;
+
+        check!(response.status_code == Some(StatusCode::OK));
+        check!(response.headers()[CONTENT_TYPE] == "application/prs.hal-forms+json");
+        check!(!response.headers().contains_key(VARY));
+        check!(body["_templates"]["default"]["method"] == "PUT");
+    }
+
+    #[tokio::test]
+    async fn negotiate_handler_without_accept() {
+        let (response, body) = negotiated(negotiable(), Request::new()).await;
+
+        check!(response.status_code == Some(StatusCode::OK));
+        check!(response.headers()[CONTENT_TYPE] == "application/prs.hal-forms+json");
+        check!(response.headers()[VARY] == "accept");
+        check!(body["_templates"]["default"]["method"] == "PUT");
+    }
+
+    #[tokio::test]
+    async fn negotiate_hal() {
+        let (response, body) = negotiated(
+            negotiable(),
+            accepting("application/hal+json, application/json;q=0.5"),
+        )
+        .await;
+
+        check!(response.status_code == Some(StatusCode::OK));
+        check!(response.headers()[CONTENT_TYPE] == "application/hal+json");
+        check!(response.headers()[VARY] == "accept");
+        assert_json_snapshot!(body, @r###"
+        {
+          "_links": {
+            "self": {
+              "href": "/test"
+            }
+          },
+          "name": "Graham"
+        }
+        "###);
+    }
+
+    #[tokio::test]
+    async fn negotiate_json() {
+        let (response, body) = negotiated(negotiable(), accepting("application/json")).await;
+
+        check!(response.status_code == Some(StatusCode::OK));
+        check!(response.headers()[CONTENT_TYPE] == "application/json");
+        check!(response.headers()[VARY] == "accept");
+        assert_json_snapshot!(body, @r###"
+        {
+          "name": "Graham"
+        }
+        "###);
+    }
+
+    #[tokio::test]
+    async fn negotiate_explicit_accept() {
+        let (response, _) = negotiated(
+            negotiable().with_accept(Accept::from_headers(
+                accepting("application/json").headers(),
+            )),
+            accepting("application/hal+json"),
+        )
+        .await;
+
+        check!(response.headers()[CONTENT_TYPE] == "application/json");
+    }
+
+    #[tokio::test]
+    async fn not_acceptable() {
+        let (response, body) = negotiated(negotiable(), accepting("image/png")).await;
+
+        check!(response.status_code == Some(StatusCode::NOT_ACCEPTABLE));
+        check!(response.headers()[VARY] == "accept");
+        check!(body == Value::Null);
+    }
+
+    #[tokio::test]
+    async fn invalid_payload() {
+        let (response, _) = render(crate::try_new(json!({"_links": "oops"}))).await;
+
+        check!(response.status_code == Some(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[tokio::test]
+    async fn invalid_cursor() {
+        let (response, _) = render(Error::InvalidCursor).await;
+
+        check!(response.status_code == Some(StatusCode::BAD_REQUEST));
+    }
+}
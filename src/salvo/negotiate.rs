@@ -0,0 +1,25 @@
+use salvo::{async_trait, Depot, FlowCtrl, Handler, Request, Response};
+
+use crate::Accept;
+
+/// Handler that negotiates the representation of every [`crate::HalResponse`] returned by the
+/// handlers after it, using the `Accept` header of the request.
+///
+/// This is the same as every handler calling [`crate::HalResponse::with_accept`] with the
+/// `Accept` header of the request, and any handler that does so anyway takes precedence. Use it as
+/// `router.hoop(http_halforms::Negotiate)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Negotiate;
+
+#[async_trait]
+impl Handler for Negotiate {
+    async fn handle(
+        &self,
+        req: &mut Request,
+        depot: &mut Depot,
+        _res: &mut Response,
+        _ctrl: &mut FlowCtrl,
+    ) {
+        depot.inject(Accept::from_headers(req.headers()));
+    }
+}
@@ -0,0 +1,354 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    hal::curie::{Curies, CURIES_REL},
+    Error, Hal, Template, TemplateProperty,
+};
+
+/// A Siren entity, either the top-level one or a sub-entity.
+#[derive(Serialize)]
+struct Entity<'a> {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    rel: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<Map<String, Value>>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    entities: Vec<Entity<'a>>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    links: Vec<SirenLink<'a>>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    actions: Vec<Action<'a>>,
+}
+
+/// A Siren link, which can have several relations.
+#[derive(Serialize)]
+struct SirenLink<'a> {
+    rel: Vec<String>,
+
+    href: &'a str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    r#type: Option<&'a str>,
+}
+
+/// A Siren action, built from a HAL-FORMS template.
+#[derive(Serialize)]
+struct Action<'a> {
+    name: &'a str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    method: Option<&'a str>,
+
+    href: &'a str,
+
+    #[serde(rename = "type")]
+    content_type: &'a str,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fields: Vec<Field<'a>>,
+}
+
+/// A single field of a Siren action, built from a HAL-FORMS template property.
+#[derive(Serialize)]
+struct Field<'a> {
+    name: &'a str,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    r#type: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
+}
+
+impl<P> Hal<P>
+where
+    P: Serialize,
+{
+    /// Convert the HAL document into a Siren entity.
+    ///
+    /// The payload becomes the `properties`, links become `links` with the relations of identical
+    /// links merged together, embedded documents become sub-entities and templates become
+    /// `actions`. CURIEs are expanded, since Siren has no equivalent. Templated links are left
+    /// out, since Siren links must be plain URIs, as are templates with no target in documents
+    /// with no self link, since Siren actions must have an `href`.
+    ///
+    /// # Errors
+    /// If the payload of the document, or of any embedded document, can't be serialized into a
+    /// JSON object.
+    pub fn to_siren(&self) -> Result<Value, Error> {
+        let entity = entity(self, vec![], &Curies::default())?;
+
+        serde_json::to_value(entity).map_err(Error::Serialization)
+    }
+}
+
+/// Build the Siren entity for a single document, with the given relations to its parent.
+fn entity<'a, P>(hal: &'a Hal<P>, rel: Vec<String>, inherited: &Curies) -> Result<Entity<'a>, Error>
+where
+    P: Serialize,
+{
+    let curies = inherited.extend_from(&hal.links);
+    let expand = |rel: &str| curies.expand(rel).unwrap_or_else(|| rel.to_string());
+
+    let properties = match serde_json::to_value(&hal.payload).map_err(Error::Serialization)? {
+        Value::Null => None,
+        Value::Object(properties) => Some(properties),
+        _ => return Err(Error::NotAnObject),
+    };
+
+    let mut links: Vec<SirenLink> = vec![];
+    for (rel, values) in hal.links.iter().filter(|(rel, _)| *rel != CURIES_REL) {
+        for link in values.iter().filter(|link| !link.templated) {
+            let existing = links.iter_mut().find(|existing| {
+                existing.href == link.href
+                    && existing.title == link.title.as_deref()
+                    && existing.r#type == link.r#type.as_deref()
+            });
+
+            match existing {
+                Some(existing) => existing.rel.push(expand(rel)),
+                None => links.push(SirenLink {
+                    rel:    vec![expand(rel)],
+                    href:   &link.href,
+                    title:  link.title.as_deref(),
+                    r#type: link.r#type.as_deref(),
+                }),
+            }
+        }
+    }
+
+    let self_href = hal
+        .links
+        .get("self")
+        .and_then(|links| links.iter().next())
+        .map(|link| link.href.as_str());
+    let actions = hal
+        .templates
+        .iter()
+        .filter_map(|(name, template)| action(name, template, self_href))
+        .collect();
+
+    let mut entities = vec![];
+    for (rel, documents) in &hal.embedded {
+        for document in documents.iter() {
+            entities.push(entity(document, vec![expand(rel)], &curies)?);
+        }
+    }
+
+    Ok(Entity {
+        rel,
+        properties,
+        entities,
+        links,
+        actions,
+    })
+}
+
+/// Build the Siren action for a single template.
+///
+/// The target of the template defaults to the self link of the document, and the content type
+/// defaults to JSON, as they do in HAL-FORMS. Siren actions must have an `href`, so if the template
+/// has no target and the document has no self link then there is no action.
+fn action<'a>(
+    name: &'a str,
+    template: &'a Template,
+    self_href: Option<&'a str>,
+) -> Option<Action<'a>> {
+    Some(Action {
+        name,
+        title: template.title.as_deref(),
+        method: template.method.as_deref(),
+        href: template.target.as_deref().or(self_href)?,
+        content_type: template
+            .content_type
+            .as_deref()
+            .unwrap_or("application/json"),
+        fields: template.properties.iter().map(field).collect(),
+    })
+}
+
+/// Build the Siren field for a single template property.
+fn field(property: &TemplateProperty) -> Field<'_> {
+    Field {
+        name:   &property.name,
+        r#type: property.r#type.as_deref(),
+        value:  property.value.as_deref(),
+        title:  property.prompt.as_deref(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::let_assert;
+    use insta::assert_json_snapshot;
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        values::{HttpMethod, TemplatePropertyType},
+        Link,
+    };
+
+    #[test]
+    fn payload_only() {
+        let_assert!(Ok(siren) = Hal::new(json!({"name": "Graham"})).to_siren());
+
+        assert_json_snapshot!(siren, @r###"
+        {
+          "properties": {
+            "name": "Graham"
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn empty_payload() {
+        let_assert!(Ok(siren) = Hal::new(()).with_link("self", "/").to_siren());
+
+        assert_json_snapshot!(siren, @r###"
+        {
+          "links": [
+            {
+              "rel": [
+                "self"
+              ],
+              "href": "/"
+            }
+          ]
+        }
+        "###);
+    }
+
+    #[test]
+    fn full_document() {
+        let hal = Hal::new(json!({"name": "Graham"}))
+            .with_link("self", Link::new("/users/1").with_title("Graham"))
+            .with_link("canonical", Link::new("/users/1").with_title("Graham"))
+            .with_link("search", Link::new("/users{?q}").templated())
+            .with_link("acme:friends", "/users/1/friends")
+            .with_curie("acme", "https://example.com/rels/{rel}")
+            .with_embedded(
+                "acme:address",
+                Hal::new(json!({"city": "Leeds"})).with_link("self", "/addresses/1"),
+            )
+            .with_template(
+                "default",
+                Template::default()
+                    .with_method(HttpMethod::PUT)
+                    .with_title("Update")
+                    .with_property(
+                        TemplateProperty::new("name")
+                            .with_prompt("Name")
+                            .with_value("Graham"),
+                    )
+                    .with_property(
+                        TemplateProperty::new("age").with_type(TemplatePropertyType::Number),
+                    ),
+            );
+
+        let_assert!(Ok(siren) = hal.to_siren());
+
+        assert_json_snapshot!(siren, @r###"
+        {
+          "properties": {
+            "name": "Graham"
+          },
+          "entities": [
+            {
+              "rel": [
+                "https://example.com/rels/address"
+              ],
+              "properties": {
+                "city": "Leeds"
+              },
+              "links": [
+                {
+                  "rel": [
+                    "self"
+                  ],
+                  "href": "/addresses/1"
+                }
+              ]
+            }
+          ],
+          "links": [
+            {
+              "rel": [
+                "https://example.com/rels/friends"
+              ],
+              "href": "/users/1/friends"
+            },
+            {
+              "rel": [
+                "canonical",
+                "self"
+              ],
+              "href": "/users/1",
+              "title": "Graham"
+            }
+          ],
+          "actions": [
+            {
+              "name": "default",
+              "title": "Update",
+              "method": "PUT",
+              "href": "/users/1",
+              "type": "application/json",
+              "fields": [
+                {
+                  "name": "name",
+                  "value": "Graham",
+                  "title": "Name"
+                },
+                {
+                  "name": "age",
+                  "type": "number"
+                }
+              ]
+            }
+          ]
+        }
+        "###);
+    }
+
+    #[test]
+    fn action_without_href() {
+        let hal = Hal::new(())
+            .with_template("default", Template::default())
+            .with_template("target", Template::default().with_target("/users"));
+
+        let_assert!(Ok(siren) = hal.to_siren());
+
+        assert_json_snapshot!(siren, @r###"
+        {
+          "actions": [
+            {
+              "name": "target",
+              "href": "/users",
+              "type": "application/json"
+            }
+          ]
+        }
+        "###);
+    }
+
+    #[test]
+    fn payload_not_an_object() {
+        let_assert!(Err(Error::NotAnObject) = Hal::new(42).to_siren());
+    }
+}
@@ -1 +1,2 @@
+pub mod enum_options;
 pub mod single_multiple;
@@ -0,0 +1,171 @@
+use serde::{
+    de::{self, DeserializeSeed, SeqAccess, Visitor},
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+
+use crate::TemplateOptions;
+
+/// Produce inline template options for a type that deserializes from an enum, or `None` for any
+/// other type.
+///
+/// The variant names are captured by deserializing the type from a probe that only supports
+/// enums, so they respect any serde renames. `Option<T>` is treated the same as `T`, and `Vec<T>`
+/// allows any number of the options to be selected instead of just one.
+#[must_use]
+pub fn enum_options<T>() -> Option<TemplateOptions>
+where
+    T: Deserialize<'static>,
+{
+    let mut probe = Probe { multiple: false };
+
+    match T::deserialize(&mut probe) {
+        Err(Found::Variants(variants)) => {
+            let options = TemplateOptions::inline(variants.to_vec());
+
+            if probe.multiple {
+                Some(options)
+            } else {
+                Some(options.with_max_items(1u32))
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Deserializer that fails for everything except enums, recording their variants.
+struct Probe {
+    multiple: bool,
+}
+
+/// Outcome of probing a type.
+#[derive(Debug, derive_more::Display, derive_more::Error)]
+enum Found {
+    #[display("enum")]
+    Variants(#[error(not(source))] &'static [&'static str]),
+    #[display("not an enum")]
+    Other,
+}
+
+impl de::Error for Found {
+    fn custom<M>(_msg: M) -> Self
+    where
+        M: std::fmt::Display,
+    {
+        Self::Other
+    }
+}
+
+impl<'de> Deserializer<'de> for &mut Probe {
+    type Error = Found;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Found::Other)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.multiple = true;
+        visitor.visit_seq(ProbeSeq(self))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Found::Variants(variants))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Sequence whose elements are all probed in turn.
+struct ProbeSeq<'a>(&'a mut Probe);
+
+impl<'de> SeqAccess<'de> for ProbeSeq<'_> {
+    type Error = Found;
+
+    fn next_element_seed<S>(&mut self, seed: S) -> Result<Option<S::Value>, Self::Error>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.0).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use assert2::{check, let_assert};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    #[allow(dead_code)]
+    enum Colour {
+        Red,
+        Green,
+        #[serde(rename = "BLUE")]
+        Blue,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Wrapped(Colour);
+
+    #[test]
+    fn enum_type() {
+        let_assert!(Some(options) = enum_options::<Colour>());
+        check!(
+            options == TemplateOptions::inline(vec!["red", "green", "BLUE"]).with_max_items(1u32)
+        );
+    }
+
+    #[test]
+    fn wrapped_enum_types() {
+        let expected = TemplateOptions::inline(vec!["red", "green", "BLUE"]);
+
+        check!(enum_options::<Option<Colour>>() == Some(expected.clone().with_max_items(1u32)));
+        check!(enum_options::<Wrapped>() == Some(expected.clone().with_max_items(1u32)));
+        check!(enum_options::<Vec<Colour>>() == Some(expected));
+    }
+
+    #[test]
+    fn other_types() {
+        check!(enum_options::<String>().is_none());
+        check!(enum_options::<u32>().is_none());
+        check!(enum_options::<Vec<String>>().is_none());
+        check!(enum_options::<Option<bool>>().is_none());
+    }
+}
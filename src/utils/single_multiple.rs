@@ -1,8 +1,8 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Representation of something that is either a single value - rendered as the value itself - or
 /// multiple values - rendered as a JSON array.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum SingleOrMultiple<T>
 where
@@ -30,6 +30,35 @@ where
         }
     }
 
+    /// Merge all of the values from another instance into this one.
+    #[must_use]
+    pub fn merge(self, other: Self) -> Self {
+        match other {
+            Self::Single(value) => self.insert(value),
+            Self::Multiple(values) => values.into_iter().fold(self, Self::insert),
+        }
+    }
+
+    /// Produce a new instance by applying the given function to every value.
+    pub fn map<'a, U, F>(&'a self, mut f: F) -> SingleOrMultiple<U>
+    where
+        U: Serialize,
+        F: FnMut(&'a T) -> U,
+    {
+        match self {
+            Self::Single(value) => SingleOrMultiple::Single(f(value)),
+            Self::Multiple(values) => SingleOrMultiple::Multiple(values.iter().map(f).collect()),
+        }
+    }
+
+    /// Generate an iterator for mutably iterating over the values.
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        match self {
+            Self::Single(value) => std::slice::from_mut(value).iter_mut(),
+            Self::Multiple(values) => values.iter_mut(),
+        }
+    }
+
     /// Generate an iterator for iterating over the values.
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         SingleOrMultipleIter {
@@ -90,6 +119,38 @@ mod tests {
         check!(values == vec![0, 1, 2]);
     }
 
+    #[test]
+    fn merge_single() {
+        let sut = SingleOrMultiple::Single(1).merge(SingleOrMultiple::Single(2));
+
+        let_assert!(SingleOrMultiple::Multiple(values) = sut);
+        check!(values == vec![1, 2]);
+    }
+
+    #[test]
+    fn merge_multiple() {
+        let sut = SingleOrMultiple::Single(1).merge(SingleOrMultiple::Multiple(vec![2, 3]));
+
+        let_assert!(SingleOrMultiple::Multiple(values) = sut);
+        check!(values == vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn map_single() {
+        let sut = SingleOrMultiple::Single(1);
+
+        let_assert!(SingleOrMultiple::Single(value) = sut.map(|v| v * 2));
+        check!(value == 2);
+    }
+
+    #[test]
+    fn map_multiple() {
+        let sut = SingleOrMultiple::Multiple(vec![1, 2]);
+
+        let_assert!(SingleOrMultiple::Multiple(values) = sut.map(|v| v * 2));
+        check!(values == vec![2, 4]);
+    }
+
     #[test]
     fn iterate_over_single() {
         let sut = SingleOrMultiple::Single(1);
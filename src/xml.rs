@@ -0,0 +1,769 @@
+use std::fmt::Display;
+
+use quick_xml::{
+    escape::escape,
+    events::{BytesStart, Event},
+    Reader,
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{Error, Hal, Link, Template};
+
+/// The elements used by the HAL document itself, which can't be used for payload fields.
+const RESERVED_NAMES: [&str; 3] = ["link", "resource", "template"];
+
+/// The attributes of links, templates and properties whose values are booleans.
+const BOOLEAN_ATTRIBUTES: [&str; 3] = ["templated", "readOnly", "required"];
+
+/// The attributes of templates, properties and options whose values are numbers.
+const NUMBER_ATTRIBUTES: [&str; 9] = [
+    "cols",
+    "max",
+    "maxLength",
+    "min",
+    "minLength",
+    "rows",
+    "step",
+    "maxItems",
+    "minItems",
+];
+
+/// A single XML element, used as an intermediate form when writing and parsing documents.
+#[derive(Debug, Default)]
+struct Element {
+    name:       String,
+    attributes: Vec<(String, String)>,
+    children:   Vec<Element>,
+    text:       String,
+}
+
+impl<P> Hal<P>
+where
+    P: Serialize,
+{
+    /// Render the HAL document as HAL+XML.
+    ///
+    /// The document is a `<resource>` element whose `href` is the self link. Links become `<link>`
+    /// elements with a `rel` attribute and an attribute for each property of the link, embedded
+    /// documents become nested `<resource>` elements with a `rel` attribute, and payload fields
+    /// become child elements, with arrays repeating the element for each value. Link hints and any
+    /// extensions that aren't simple values have no equivalent and are left out.
+    ///
+    /// HAL-FORMS templates use an extension to HAL+XML. Each template becomes a `<template>`
+    /// element with a `name` attribute, containing a `<property>` element for each property. The
+    /// options of a property become an `<options>` element, containing an `<option>` element for
+    /// each inline option, a `<link>` element for remote options and a `<selected>` element for
+    /// each selected value. Everything else is an attribute named as in HAL-FORMS.
+    ///
+    /// # Errors
+    /// If the payload of the document, or of any embedded document, can't be serialized into a
+    /// JSON object, or contains a key that can't be used as an element name. The `link`,
+    /// `resource` and `template` elements are reserved for the document itself.
+    pub fn to_xml(&self) -> Result<String, Error> {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        resource(self, None)?.write(&mut xml, 0);
+
+        Ok(xml)
+    }
+}
+
+impl Hal<Value> {
+    /// Parse a HAL+XML document, as written by [`Hal::to_xml`].
+    ///
+    /// XML has no types, so every payload field is parsed as a string, or as an object if the
+    /// element has child elements. Repeated elements become an array, and so an array with only a
+    /// single value is parsed as that value. Embedded documents are parsed in the same way, and
+    /// any link relations in CURIE form are expanded and compacted again when serializing, as when
+    /// deserializing from JSON.
+    ///
+    /// # Errors
+    /// If the document isn't well-formed XML, the root element isn't a `<resource>`, or any link,
+    /// embedded document or template is missing required attributes or has invalid values.
+    pub fn from_xml(xml: &str) -> Result<Self, Error> {
+        let root = Element::parse(xml)?;
+        if root.name != "resource" {
+            return Err(Error::InvalidXml(format!(
+                "root element must be <resource>, not <{}>",
+                root.name
+            )));
+        }
+
+        document(root).map(Hal::expand_parsed_curies)
+    }
+}
+
+/// Build the `<resource>` element for a single document, with the given relation to its parent.
+fn resource<P>(hal: &Hal<P>, rel: Option<&str>) -> Result<Element, Error>
+where
+    P: Serialize,
+{
+    let mut element = Element::new("resource");
+    if let Some(rel) = rel {
+        element.attribute("rel", rel);
+    }
+
+    let self_link = hal.links.get("self").and_then(|links| links.iter().next());
+    if let Some(link) = self_link {
+        element.attribute("href", &link.href);
+    }
+
+    for (rel, links) in &hal.links {
+        for (index, link) in links.iter().enumerate() {
+            // A self link with nothing but an href is already the href of the resource.
+            if rel == "self" && index == 0 && *link == Link::new(&link.href) {
+                continue;
+            }
+
+            let mut child = Element::new("link");
+            child.attribute("rel", rel);
+            child.attributes_from(serde_json::to_value(link).map_err(Error::Serialization)?);
+            element.children.push(child);
+        }
+    }
+
+    for (rel, documents) in &hal.embedded {
+        for document in documents.iter() {
+            element.children.push(resource(document, Some(rel))?);
+        }
+    }
+
+    for (name, template) in &hal.templates {
+        element.children.push(template_element(name, template)?);
+    }
+
+    let payload = match serde_json::to_value(&hal.payload).map_err(Error::Serialization)? {
+        Value::Null => Map::new(),
+        Value::Object(payload) => payload,
+        _ => return Err(Error::NotAnObject),
+    };
+    for (key, value) in payload {
+        if RESERVED_NAMES.contains(&key.as_str()) {
+            return Err(Error::ReservedKey(key));
+        }
+        if !is_name(&key) {
+            return Err(Error::InvalidXmlName(key));
+        }
+
+        field_elements(&mut element.children, &key, value)?;
+    }
+
+    Ok(element)
+}
+
+/// Build the `<template>` element for a single template.
+fn template_element(name: &str, template: &Template) -> Result<Element, Error> {
+    let mut element = Element::new("template");
+    element.attribute("name", name);
+
+    let mut fields = serde_json::to_value(template).map_err(Error::Serialization)?;
+    if let Some(Value::Array(properties)) = fields.get_mut("properties").map(Value::take) {
+        for property in properties {
+            let mut child = Element::new("property");
+            if let Some(Value::Object(options)) = property.get("options") {
+                child.children.push(options_element(options.clone()));
+            }
+            child.attributes_from(property);
+            element.children.push(child);
+        }
+    }
+    element.attributes_from(fields);
+
+    Ok(element)
+}
+
+/// Build the `<options>` element for the options of a single template property.
+fn options_element(mut options: Map<String, Value>) -> Element {
+    let mut element = Element::new("options");
+
+    if let Some(Value::Array(inline)) = options.remove("inline") {
+        for option in inline {
+            let mut child = Element::new("option");
+            match option {
+                Value::String(value) => child.attribute("value", &value),
+                option => child.attributes_from(option),
+            }
+            element.children.push(child);
+        }
+    }
+
+    if let Some(link) = options.remove("link") {
+        let mut child = Element::new("link");
+        child.attributes_from(link);
+        element.children.push(child);
+    }
+
+    if let Some(Value::Array(selected)) = options.remove("selectedValues") {
+        for value in selected {
+            let mut child = Element::new("selected");
+            child.text = scalar(&value).unwrap_or_default();
+            element.children.push(child);
+        }
+    }
+
+    element.attributes_from(Value::Object(options));
+
+    element
+}
+
+/// Build the elements for a single payload field, repeating the element for each value of an
+/// array. The keys of nested objects must be valid XML names, as for the payload itself.
+fn field_elements(elements: &mut Vec<Element>, name: &str, value: Value) -> Result<(), Error> {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                field_elements(elements, name, value)?;
+            }
+        },
+        Value::Object(fields) => {
+            let mut element = Element::new(name);
+            for (key, value) in fields {
+                if !is_name(&key) {
+                    return Err(Error::InvalidXmlName(key));
+                }
+                field_elements(&mut element.children, &key, value)?;
+            }
+            elements.push(element);
+        },
+        value => {
+            let mut element = Element::new(name);
+            element.text = scalar(&value).unwrap_or_default();
+            elements.push(element);
+        },
+    }
+
+    Ok(())
+}
+
+/// Build a HAL document from a `<resource>` element.
+fn document(element: Element) -> Result<Hal<Value>, Error> {
+    let mut hal = Hal::new(Value::Null);
+    let mut payload = Map::new();
+
+    let href = element.get("href").map(ToString::to_string);
+
+    for mut child in element.children {
+        match child.name.as_str() {
+            "link" => {
+                let rel = child.take("rel")?;
+                let link: Link = from_attributes(child.attributes)?;
+                hal = hal.with_link(rel, link);
+            },
+            "resource" => {
+                let rel = child.take("rel")?;
+                hal = hal.with_embedded(rel, document(child)?);
+            },
+            "template" => {
+                let name = child.take("name")?;
+                hal = hal.with_template(name, template(child)?);
+            },
+            _ => {
+                let name = std::mem::take(&mut child.name);
+                let value = field(child);
+
+                match payload.get_mut(&name) {
+                    None => {
+                        payload.insert(name, value);
+                    },
+                    Some(Value::Array(values)) => values.push(value),
+                    Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+                }
+            },
+        }
+    }
+
+    // An explicit self link element takes priority over the href of the resource.
+    if let Some(href) = href.filter(|_| !hal.links.contains_key("self")) {
+        hal = hal.with_link("self", href);
+    }
+
+    hal.payload = Value::Object(payload);
+
+    Ok(hal)
+}
+
+/// Build a template from a `<template>` element.
+fn template(element: Element) -> Result<Template, Error> {
+    let mut fields = typed_attributes(element.attributes);
+
+    let properties = element
+        .children
+        .into_iter()
+        .filter(|child| child.name == "property")
+        .map(|child| {
+            let mut property = typed_attributes(child.attributes);
+            if let Some(options) = child.children.into_iter().find(|c| c.name == "options") {
+                property.insert("options".to_string(), options_value(options));
+            }
+
+            Value::Object(property)
+        })
+        .collect();
+    fields.insert("properties".to_string(), Value::Array(properties));
+
+    serde_json::from_value(Value::Object(fields)).map_err(invalid)
+}
+
+/// Build the HAL-FORMS options of a property from an `<options>` element.
+fn options_value(element: Element) -> Value {
+    let mut options = typed_attributes(element.attributes);
+    let mut inline = vec![];
+    let mut selected = vec![];
+
+    for child in element.children {
+        match child.name.as_str() {
+            "option" => inline.push(Value::Object(typed_attributes(child.attributes))),
+            "link" => {
+                options.insert(
+                    "link".to_string(),
+                    Value::Object(typed_attributes(child.attributes)),
+                );
+            },
+            "selected" => selected.push(Value::String(child.text)),
+            _ => {},
+        }
+    }
+
+    if !options.contains_key("link") {
+        options.insert("inline".to_string(), Value::Array(inline));
+    }
+    options.insert("selectedValues".to_string(), Value::Array(selected));
+
+    Value::Object(options)
+}
+
+/// Build the value of a payload field from its element.
+fn field(element: Element) -> Value {
+    if element.children.is_empty() {
+        return Value::String(element.text);
+    }
+
+    let mut fields = Map::new();
+    for child in element.children {
+        let name = child.name.clone();
+        let value = field(child);
+
+        match fields.get_mut(&name) {
+            None => {
+                fields.insert(name, value);
+            },
+            Some(Value::Array(values)) => values.push(value),
+            Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+        }
+    }
+
+    Value::Object(fields)
+}
+
+/// Deserialize a value from the attributes of an element.
+fn from_attributes<T>(attributes: Vec<(String, String)>) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_json::from_value(Value::Object(typed_attributes(attributes))).map_err(invalid)
+}
+
+/// Convert the attributes of an element into a JSON object, using the type that HAL and HAL-FORMS
+/// expect for each attribute.
+fn typed_attributes(attributes: Vec<(String, String)>) -> Map<String, Value> {
+    attributes
+        .into_iter()
+        .map(|(name, value)| {
+            let value = if BOOLEAN_ATTRIBUTES.contains(&name.as_str()) {
+                value.parse().map_or(Value::String(value), Value::Bool)
+            } else if NUMBER_ATTRIBUTES.contains(&name.as_str()) {
+                value
+                    .parse::<u64>()
+                    .map_or(Value::String(value), Value::from)
+            } else {
+                Value::String(value)
+            };
+
+            (name, value)
+        })
+        .collect()
+}
+
+/// Render a simple JSON value as text, or `None` if it isn't a simple value.
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some(String::new()),
+        Value::String(value) => Some(value.clone()),
+        Value::Bool(value) => Some(value.to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+/// Determine whether a string can be used as the name of an element.
+///
+/// This is deliberately stricter than XML itself, and doesn't allow namespace prefixes.
+fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Build the error for a document that can't be parsed.
+fn invalid<E>(error: E) -> Error
+where
+    E: Display,
+{
+    Error::InvalidXml(error.to_string())
+}
+
+impl Element {
+    fn new<S>(name: S) -> Self
+    where
+        S: ToString,
+    {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Add an attribute to the element.
+    fn attribute(&mut self, name: &str, value: &str) {
+        self.attributes.push((name.to_string(), value.to_string()));
+    }
+
+    /// Add an attribute for every simple value of a JSON object.
+    fn attributes_from(&mut self, value: Value) {
+        if let Value::Object(fields) = value {
+            for (name, value) in fields {
+                if value.is_null() || !is_name(&name) {
+                    continue;
+                }
+                if let Some(value) = scalar(&value) {
+                    self.attributes.push((name, value));
+                }
+            }
+        }
+    }
+
+    /// Get the value of an attribute.
+    fn get(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Remove a required attribute from the element, returning its value.
+    fn take(&mut self, name: &str) -> Result<String, Error> {
+        let index = self
+            .attributes
+            .iter()
+            .position(|(n, _)| n == name)
+            .ok_or_else(|| {
+                Error::InvalidXml(format!("<{}> is missing the {name} attribute", self.name))
+            })?;
+
+        Ok(self.attributes.remove(index).1)
+    }
+
+    /// Write the element, and everything inside it, indented to the given depth.
+    fn write(&self, xml: &mut String, depth: usize) {
+        for _ in 0..depth {
+            xml.push_str("  ");
+        }
+
+        xml.push('<');
+        xml.push_str(&self.name);
+        for (name, value) in &self.attributes {
+            xml.push(' ');
+            xml.push_str(name);
+            xml.push_str("=\"");
+            xml.push_str(&escape(value.as_str()));
+            xml.push('"');
+        }
+
+        if self.children.is_empty() && self.text.is_empty() {
+            xml.push_str("/>\n");
+            return;
+        }
+
+        xml.push('>');
+        if self.children.is_empty() {
+            xml.push_str(&escape(self.text.as_str()));
+        } else {
+            xml.push('\n');
+            for child in &self.children {
+                child.write(xml, depth + 1);
+            }
+            for _ in 0..depth {
+                xml.push_str("  ");
+            }
+        }
+        xml.push_str("</");
+        xml.push_str(&self.name);
+        xml.push_str(">\n");
+    }
+
+    /// Parse an XML document into its root element.
+    fn parse(xml: &str) -> Result<Self, Error> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().trim_text(true);
+
+        let mut stack: Vec<Self> = vec![];
+        loop {
+            let element = match reader.read_event().map_err(invalid)? {
+                Event::Start(start) => {
+                    stack.push(Self::from_start(&start)?);
+                    continue;
+                },
+                Event::Empty(start) => Self::from_start(&start)?,
+                Event::End(_) => stack
+                    .pop()
+                    .ok_or_else(|| Error::InvalidXml("unexpected end tag".to_string()))?,
+                Event::Text(text) => {
+                    if let Some(parent) = stack.last_mut() {
+                        parent.text.push_str(&text.unescape().map_err(invalid)?);
+                    }
+                    continue;
+                },
+                Event::CData(data) => {
+                    if let Some(parent) = stack.last_mut() {
+                        parent
+                            .text
+                            .push_str(&String::from_utf8_lossy(&data.into_inner()));
+                    }
+                    continue;
+                },
+                Event::Eof => return Err(Error::InvalidXml("no root element".to_string())),
+                _ => continue,
+            };
+
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(element),
+                None => return Ok(element),
+            }
+        }
+    }
+
+    /// Build an element, without any content, from its start tag.
+    fn from_start(start: &BytesStart) -> Result<Self, Error> {
+        let mut element = Self::new(String::from_utf8_lossy(start.name().as_ref()));
+
+        for attribute in start.attributes() {
+            let attribute = attribute.map_err(invalid)?;
+            element.attributes.push((
+                String::from_utf8_lossy(attribute.key.as_ref()).into_owned(),
+                attribute.unescape_value().map_err(invalid)?.into_owned(),
+            ));
+        }
+
+        Ok(element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use assert2::{check, let_assert};
+    use insta::{assert_json_snapshot, assert_snapshot};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{values::HttpMethod, InlineOption, TemplateOptions, TemplateProperty};
+
+    /// A document using everything that HAL+XML can represent.
+    fn document() -> Hal<Value> {
+        Hal::new(json!({
+            "name": "Graham & Co",
+            "age": 42,
+            "tags": ["a", "b"],
+            "address": {"city": "Leeds"},
+        }))
+        .with_link("self", "/users/1")
+        .with_link(
+            "friends",
+            Link::new("/users/1/friends")
+                .with_title("<Friends>")
+                .with_type("application/hal+xml"),
+        )
+        .with_link("search", Link::new("/users{?q}").templated())
+        .with_embedded(
+            "address",
+            Hal::new(json!({"city": "Leeds"})).with_link("self", "/addresses/1"),
+        )
+        .with_template(
+            "default",
+            Template::default()
+                .with_method(HttpMethod::PUT)
+                .with_property(
+                    TemplateProperty::new("name")
+                        .required()
+                        .with_max_length(50_u32),
+                )
+                .with_property(TemplateProperty::new("role").with_options(
+                    TemplateOptions::Inline {
+                        inline:          vec![
+                            InlineOption::new("admin").with_prompt("Admin"),
+                            InlineOption::new("user"),
+                        ],
+                        max_items:       Some(1),
+                        min_items:       None,
+                        selected_values: vec!["user".to_string()],
+                        extensions:      BTreeMap::default(),
+                    },
+                )),
+        )
+    }
+
+    #[test]
+    fn full_document() {
+        let_assert!(Ok(xml) = document().to_xml());
+
+        assert_snapshot!(xml, @r###"
+        <?xml version="1.0" encoding="UTF-8"?>
+        <resource href="/users/1">
+          <link rel="friends" href="/users/1/friends" type="application/hal+xml" title="&lt;Friends&gt;"/>
+          <link rel="search" href="/users{?q}" templated="true"/>
+          <resource rel="address" href="/addresses/1">
+            <city>Leeds</city>
+          </resource>
+          <template name="default" method="PUT">
+            <property name="name" required="true" maxLength="50"/>
+            <property name="role">
+              <options maxItems="1">
+                <option prompt="Admin" value="admin"/>
+                <option value="user"/>
+                <selected>user</selected>
+              </options>
+            </property>
+          </template>
+          <name>Graham &amp; Co</name>
+          <age>42</age>
+          <tags>a</tags>
+          <tags>b</tags>
+          <address>
+            <city>Leeds</city>
+          </address>
+        </resource>
+        "###);
+    }
+
+    #[test]
+    fn round_trip() {
+        let_assert!(Ok(xml) = document().to_xml());
+        let_assert!(Ok(hal) = Hal::from_xml(&xml));
+
+        assert_json_snapshot!(hal, @r###"
+        {
+          "_links": {
+            "friends": {
+              "href": "/users/1/friends",
+              "type": "application/hal+xml",
+              "title": "<Friends>"
+            },
+            "search": {
+              "href": "/users{?q}",
+              "templated": true
+            },
+            "self": {
+              "href": "/users/1"
+            }
+          },
+          "_embedded": {
+            "address": {
+              "_links": {
+                "self": {
+                  "href": "/addresses/1"
+                }
+              },
+              "city": "Leeds"
+            }
+          },
+          "_templates": {
+            "default": {
+              "method": "PUT",
+              "properties": [
+                {
+                  "name": "name",
+                  "required": true,
+                  "maxLength": 50
+                },
+                {
+                  "name": "role",
+                  "options": {
+                    "inline": [
+                      {
+                        "prompt": "Admin",
+                        "value": "admin"
+                      },
+                      {
+                        "value": "user"
+                      }
+                    ],
+                    "maxItems": 1,
+                    "selectedValues": [
+                      "user"
+                    ]
+                  }
+                }
+              ]
+            }
+          },
+          "name": "Graham & Co",
+          "age": "42",
+          "tags": [
+            "a",
+            "b"
+          ],
+          "address": {
+            "city": "Leeds"
+          }
+        }
+        "###);
+    }
+
+    #[test]
+    fn self_link_with_attributes() {
+        let hal = Hal::new(()).with_link("self", Link::new("/").with_title("Home"));
+
+        let_assert!(Ok(xml) = hal.to_xml());
+        check!(xml.contains(r#"<resource href="/">"#));
+        check!(xml.contains(r#"<link rel="self" href="/" title="Home"/>"#));
+
+        let_assert!(Ok(parsed) = Hal::from_xml(&xml));
+        check!(parsed.links.get("self").unwrap().iter().count() == 1);
+    }
+
+    #[test]
+    fn invalid_payloads() {
+        let_assert!(Err(Error::NotAnObject) = Hal::new(42).to_xml());
+        let_assert!(Err(Error::ReservedKey(key)) = Hal::new(json!({"link": 1})).to_xml());
+        check!(key == "link");
+        let_assert!(Err(Error::InvalidXmlName(key)) = Hal::new(json!({"a b": 1})).to_xml());
+        check!(key == "a b");
+
+        let_assert!(
+            Err(Error::InvalidXmlName(key)) =
+                Hal::new(json!({"address": {"a><evil/><b": "x"}})).to_xml()
+        );
+        check!(key == "a><evil/><b");
+        let_assert!(
+            Err(Error::InvalidXmlName(key)) =
+                Hal::new(json!({"addresses": [{"street": {"ok key": 1}}]})).to_xml()
+        );
+        check!(key == "ok key");
+    }
+
+    #[test]
+    fn invalid_documents() {
+        let_assert!(Err(Error::InvalidXml(_)) = Hal::from_xml(""));
+        let_assert!(Err(Error::InvalidXml(_)) = Hal::from_xml("<resource><a></b></resource>"));
+        let_assert!(Err(Error::InvalidXml(_)) = Hal::from_xml("<document/>"));
+        let_assert!(
+            Err(Error::InvalidXml(_)) = Hal::from_xml(r#"<resource><link href="/"/></resource>"#)
+        );
+        let_assert!(
+            Err(Error::InvalidXml(_)) =
+                Hal::from_xml(r#"<resource><template name="t"><property/></template></resource>"#)
+        );
+    }
+}